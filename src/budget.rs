@@ -0,0 +1,117 @@
+use quanta::Instant;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Budget Struct
+/// time budget and cancellation of a generation, covering saturation,
+/// grammar creation and every extraction round, clones share the
+/// cancellation flag, so a clone can cancel the generation from another thread
+#[derive(Clone, Debug)]
+pub struct Budget {
+    /// start time of the budget
+    start_time: Instant,
+    /// time limit, None for no time limit
+    time_limit: Option<Duration>,
+    /// cancellation flag shared by all clones
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        return Budget::new(None);
+    }
+}
+
+impl Budget {
+    /// ### constructor, the budget starts now
+    /// #### Argument
+    /// * `time_limit` - time limit, None for no time limit
+    /// #### Return
+    /// * `Budget`
+    pub fn new(time_limit: Option<Duration>) -> Self {
+        return Budget { start_time: Instant::now(), time_limit, cancelled: Default::default() };
+    }
+
+    /// ### member function to create a budget of 1 step (e.g. 1 extraction round)
+    /// ### starting now, the time limit is capped by the remaining time
+    /// ### and the cancellation flag is shared with this budget
+    /// #### Arguments
+    /// * `self`
+    /// * `time_limit` - time limit of the step
+    /// #### Return
+    /// * `Budget` - budget of the step
+    pub fn sub_budget(&self, time_limit: Duration) -> Budget {
+        let time_limit = match self.remaining() {
+            Some(remaining) => { time_limit.min(remaining) },
+            None => { time_limit },
+        };
+
+        return Budget { start_time: Instant::now(), time_limit: Some(time_limit), cancelled: self.cancelled.clone() };
+    }
+
+    /// ### member function to cancel the generation,
+    /// ### the generation stops at its next check
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `None`
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        return;
+    }
+
+    /// ### member function to check if the budget is cancelled
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `bool` - whether the budget is cancelled or not
+    pub fn is_cancelled(&self) -> bool { return self.cancelled.load(Ordering::Relaxed); }
+
+    /// ### member function to get the elapsed time since the budget started
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `Duration` - elapsed time
+    pub fn elapsed(&self) -> Duration { return self.start_time.elapsed(); }
+
+    /// ### member function to get the remaining time
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `Option<Duration>` - remaining time, None for no time limit
+    pub fn remaining(&self) -> Option<Duration> {
+        return self.time_limit.map(|time_limit| time_limit.saturating_sub(self.elapsed()));
+    }
+
+    /// ### member function to check if the budget is exhausted,
+    /// ### i.e. cancelled or out of time
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `bool` - whether the budget is exhausted or not
+    pub fn is_exhausted(&self) -> bool {
+        return self.is_cancelled() || self.remaining() == Some(Duration::ZERO);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budget_cancellation() {
+        let budget = Budget::default();
+        let round = budget.sub_budget(Duration::from_secs(60));
+        assert!(!budget.is_exhausted() && !round.is_exhausted());
+        assert_eq!(budget.remaining(), None);
+
+        let handle = budget.clone();
+        std::thread::spawn(move || handle.cancel()).join().unwrap();
+        assert!(budget.is_cancelled() && round.is_exhausted());
+
+        let budget = Budget::new(Some(Duration::from_secs(60)));
+        assert!(budget.sub_budget(Duration::from_secs(600)).remaining().unwrap() <= Duration::from_secs(60));
+        assert!(Budget::new(Some(Duration::ZERO)).is_exhausted());
+    }
+}
@@ -0,0 +1,338 @@
+use crate::*;
+use ordered_float::NotNan;
+use std::cmp::Ordering;
+
+/// maximum # of variables renamed in every order by alpha_rename
+pub const MAX_ALPHA_VARS: usize = 5;
+
+/// Canonicalization Struct
+/// equivalences of the dedupe of equivalent expressions on top of
+/// commutativity and associativity of + and *, default is AC only
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Canonicalization {
+    /// equal modulo variable renaming, e.g. `sin x` and `sin y`
+    pub alpha_rename: bool,
+    /// equal modulo numeric literal spelling, e.g. `0.5`, `1/2` and `/ 1 2`
+    pub normalize_constants: bool,
+}
+
+impl Canonicalization {
+    /// ### member function to canonicalize an expression, constants are
+    /// ### normalized first, then variables are renamed, then + and * are
+    /// ### canonicalized (see ac_canonicalize)
+    /// #### Arguments
+    /// * `self`
+    /// * `expr` - expression
+    /// #### Return
+    /// * `RecExpr<Math>` - canonical expression
+    pub fn canonicalize(&self, expr: &RecExpr<Math>) -> RecExpr<Math> {
+        let mut expr = expr.clone();
+        if self.normalize_constants { expr = normalize_constants(&expr); }
+        if self.alpha_rename { expr = alpha_rename(&expr); }
+
+        return ac_canonicalize(&expr);
+    }
+
+    /// ### member function to get the key of an expression in prefix notation
+    /// ### shared by all expressions with the same canonical expression,
+    /// ### an expression that cannot be parsed is its own key
+    /// #### Arguments
+    /// * `self`
+    /// * `expr` - expression in prefix notation, e.g. "+ x * 2 y"
+    /// #### Return
+    /// * `String` - canonical expression (S-expression)
+    pub fn key(&self, expr: &str) -> String {
+        return match prefix_to_recexpr(expr) {
+            Ok(expr) => { self.canonicalize(&expr).to_string() },
+            Err(_) => { expr.to_string() },
+        };
+    }
+
+    /// ### member function to remove the duplicates from the final results
+    /// ### of equivalent expressions, the first expression (see sort_exprs)
+    /// ### of every canonical expression is kept
+    /// #### Arguments
+    /// * `self`
+    /// * `equiv_exprs` - deduplicate results of equivalent expressions
+    /// #### Return
+    /// * `HashSet<String>` - distinct equivalent expressions
+    pub fn rm_dupes(&self, equiv_exprs: &HashSet<String>) -> HashSet<String> {
+        let mut expr_mapping = HashMap::default();
+
+        /* sorted expressions keep the same representative on every run */
+        for expr in sort_exprs(equiv_exprs) {
+            expr_mapping.entry(self.key(expr)).or_insert_with(|| expr.clone());
+        }
+
+        return expr_mapping.into_values().collect();
+    }
+}
+
+/// ### function to check if an operator is commutative and associative
+/// #### Argument
+/// * `enode` - operator (or leaf)
+/// #### Return
+/// * `bool` - whether the operator is + or * or not
+pub fn is_ac(enode: &Math) -> bool {
+    return matches!(enode, Math::Add(_) | Math::Mul(_));
+}
+
+/// ### function to compare 2 subexpressions structurally,
+/// ### by operator (or leaf) first, then by children from left to right
+/// #### Arguments
+/// * `expr` - expression
+/// * `lhs` - root of 1st subexpression
+/// * `rhs` - root of 2nd subexpression
+/// #### Return
+/// * `Ordering` - order of the subexpressions
+pub fn cmp_subexpr(expr: &RecExpr<Math>, lhs: Id, rhs: Id) -> Ordering {
+    if lhs == rhs { return Ordering::Equal; }
+
+    return expr[lhs].to_string().cmp(&expr[rhs].to_string())
+        .then_with(|| expr[lhs].len().cmp(&expr[rhs].len()))
+        .then_with(|| {
+            return expr[lhs].children().iter().zip(expr[rhs].children())
+                .map(|(lhs, rhs)| cmp_subexpr(expr, *lhs, *rhs))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal);
+        });
+}
+
+/// ### function to canonicalize an expression modulo commutativity and
+/// ### associativity of + and *, every + (*) chain is flattened, its operands
+/// ### are sorted by cmp_subexpr and rebuilt right-nested, e.g.
+/// ### `(+ (+ z y) x)` and `(+ x (+ y z))` both become `(+ x (+ y z))`,
+/// ### the operands of other operators (e.g. - and /) keep their order
+/// #### Argument
+/// * `expr` - expression
+/// #### Return
+/// * `RecExpr<Math>` - canonical expression
+pub fn ac_canonicalize(expr: &RecExpr<Math>) -> RecExpr<Math> {
+    /// ### function to collect the operands of the AC chain of an operator
+    fn collect(expr: &RecExpr<Math>, op: &Math, id: Id, operands: &mut Vec<Id>) {
+        if expr[id].matches(op) {
+            for child in expr[id].children() {
+                collect(expr, op, *child, operands);
+            }
+        } else {
+            operands.push(id);
+        }
+    }
+
+    /// ### function to add the canonical subexpression at a node
+    fn add(expr: &RecExpr<Math>, id: Id, canonical: &mut RecExpr<Math>) -> Id {
+        let enode = &expr[id];
+        if !is_ac(enode) {
+            let enode = enode.clone().map_children(|child| add(expr, child, canonical));
+            return canonical.add(enode);
+        }
+
+        let mut chain = vec![];
+        for child in enode.children() {
+            collect(expr, enode, *child, &mut chain);
+        }
+        let mut operands: Vec<Id> = chain.into_iter().map(|operand| add(expr, operand, canonical)).collect();
+        operands.sort_by(|lhs, rhs| cmp_subexpr(canonical, *lhs, *rhs));
+
+        let mut root = operands.pop().unwrap();
+        while let Some(operand) = operands.pop() {
+            let mut children = [operand, root].into_iter();
+            root = canonical.add(enode.clone().map_children(|_| children.next().unwrap()));
+        }
+
+        return root;
+    }
+
+    let mut canonical = RecExpr::default();
+    add(expr, expr.root(), &mut canonical);
+
+    return canonical;
+}
+
+/// ### function to rename the variables of an expression to v0, v1, ...,
+/// ### the renaming of the AC canonical expression (see ac_canonicalize)
+/// ### smallest over all orders of the variables is kept, so the result does
+/// ### not depend on the names or on the operand order of + and *, beyond
+/// ### MAX_ALPHA_VARS variables they are renamed by their first occurrence
+/// ### in the AC canonical expression
+/// #### Argument
+/// * `expr` - expression
+/// #### Return
+/// * `RecExpr<Math>` - renamed expression, AC canonical
+pub fn alpha_rename(expr: &RecExpr<Math>) -> RecExpr<Math> {
+    /// ### function to rename the i-th variable of an order to vi
+    fn rename(expr: &RecExpr<Math>, order: &[Symbol]) -> RecExpr<Math> {
+        let nodes: Vec<Math> = expr.as_ref().iter().map(|enode| match enode {
+            Math::Symbol(name) => {
+                let idx = order.iter().position(|var| var == name).unwrap();
+                Math::Symbol(Symbol::from(format!("v{}", idx)))
+            },
+            enode => { enode.clone() },
+        }).collect();
+
+        return ac_canonicalize(&RecExpr::from(nodes));
+    }
+
+    /// ### function to get all orders of the variables
+    fn permutations(vars: &[Symbol]) -> Vec<Vec<Symbol>> {
+        if vars.is_empty() { return vec![vec![]]; }
+
+        let mut orders = vec![];
+        for (idx, var) in vars.iter().enumerate() {
+            let mut rest = vars.to_vec();
+            rest.remove(idx);
+            for mut order in permutations(&rest) {
+                order.insert(0, *var);
+                orders.push(order);
+            }
+        }
+
+        return orders;
+    }
+
+    let expr = ac_canonicalize(expr);
+    let mut vars: Vec<Symbol> = vec![];
+    for token in recexpr_to_tokens(&expr) {
+        if let GrammarToken::Terminal(Math::Symbol(name)) = token {
+            if !vars.contains(&name) { vars.push(name); }
+        }
+    }
+    if vars.len() > MAX_ALPHA_VARS { return rename(&expr, &vars); }
+
+    return permutations(&vars)
+        .iter()
+        .map(|order| rename(&expr, order))
+        .min_by_key(|renamed| renamed.to_string())
+        .unwrap();
+}
+
+/// ### function to normalize the spellings of numeric literals,
+/// ### fractions `a/b` and divisions `/ a b` of constants are folded
+/// ### into 1 constant and -0 becomes 0, fractions and divisions
+/// ### by 0 or out of range stay as they are
+/// #### Argument
+/// * `expr` - expression
+/// #### Return
+/// * `RecExpr<Math>` - normalized expression
+pub fn normalize_constants(expr: &RecExpr<Math>) -> RecExpr<Math> {
+    /// ### function to get the finite constant of a numeric literal
+    fn constant(enode: &Math) -> Option<f64> {
+        let value = match enode {
+            Math::Constant(c) => { c.into_inner() },
+            Math::Symbol(name) => {
+                let (num, den) = name.as_str().split_once('/')?;
+                let den = den.parse::<f64>().ok()?;
+                if den == 0.0 { return None; }
+                num.parse::<f64>().ok()? / den
+            },
+            _ => { return None; },
+        };
+
+        return if value.is_finite() { Some(value) } else { None };
+    }
+
+    let mut normalized: Vec<Math> = Vec::with_capacity(expr.as_ref().len());
+    for enode in expr.as_ref() {
+        let value = match enode {
+            Math::Div([num, den]) => {
+                match (constant(&normalized[usize::from(*num)]), constant(&normalized[usize::from(*den)])) {
+                    (Some(num), Some(den)) if den != 0.0 => { Some(num / den) },
+                    _ => { None },
+                }
+            },
+            Math::Symbol(_) => { constant(enode) },
+            Math::Constant(c) => { Some(c.into_inner()) },
+            _ => { None },
+        };
+        /* -0 + 0 turns -0 into 0 */
+        normalized.push(match value.filter(|value| value.is_finite()).and_then(|value| NotNan::new(value + 0.0).ok()) {
+            Some(c) => { Math::Constant(c) },
+            None => { enode.clone() },
+        });
+    }
+
+    return RecExpr::from(normalized);
+}
+
+/// ### function to get the key of an expression in prefix notation shared by
+/// ### all expressions equal to it modulo commutativity and associativity,
+/// ### an expression that cannot be parsed is its own key
+/// #### Argument
+/// * `expr` - expression in prefix notation, e.g. "+ x * 2 y"
+/// #### Return
+/// * `String` - canonical expression (S-expression)
+pub fn ac_key(expr: &str) -> String {
+    return Canonicalization::default().key(expr);
+}
+
+/// ### function to remove duplicates modulo commutativity and
+/// ### associativity of + and * from the final results of equivalent
+/// ### expressions, the first expression (see sort_exprs) of every
+/// ### canonical expression is kept
+/// #### Argument
+/// * `equiv_exprs` - deduplicate results of equivalent expressions
+/// #### Return
+/// * `HashSet<String>` - distinct equivalent expressions
+pub fn rm_ac_dupes(equiv_exprs: &HashSet<String>) -> HashSet<String> {
+    return Canonicalization::default().rm_dupes(equiv_exprs);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ac_canonical_dedupe() {
+        assert_eq!(ac_key("+ + z y x"), ac_key("+ x + y z"));
+        assert_eq!(ac_key("+ x + y z"), "(+ x (+ y z))");
+        assert_eq!(ac_key("* sin x 2"), ac_key("* 2 sin x"));
+        assert_ne!(ac_key("- x y"), ac_key("- y x"));
+        assert_ne!(ac_key("/ a b"), ac_key("/ b a"));
+        assert_ne!(ac_key("+ x * y z"), ac_key("* x + y z"));
+        /* operands of nested chains are sorted too */
+        assert_eq!(ac_key("* + y x + b a"), ac_key("* + a b + x y"));
+
+        let equiv_exprs: HashSet<String> = ["- x y", "- y x", "+ x y", "+ y x", "+ 1 + x y", "+ + y 1 x"]
+            .into_iter()
+            .map(|expr| expr.to_string())
+            .collect();
+        let distinct = rm_ac_dupes(&equiv_exprs);
+        assert_eq!(distinct.len(), 4);
+        assert!(distinct.contains("- x y") && distinct.contains("- y x") && distinct.contains("+ x y"));
+    }
+
+    #[test]
+    fn alpha_constant_dedupe() {
+        let alpha = Canonicalization { alpha_rename: true, normalize_constants: false };
+        assert_eq!(alpha.key("sin x"), alpha.key("sin y"));
+        assert_eq!(alpha.key("+ x sin y"), alpha.key("+ sin x y"));
+        assert_eq!(alpha.key("- x y"), alpha.key("- y x"));
+        assert_eq!(ac_key("+ * x y * y y"), ac_key("+ * y y * x y"));
+        assert_eq!(alpha.key("+ * x y * y y"), alpha.key("+ * y y * x y"));
+        assert_eq!(alpha.key("+ * x y * y y"), alpha.key("+ * b a * b b"));
+        /* beyond MAX_ALPHA_VARS variables, AC-equal expressions still share a key */
+        assert_eq!(alpha.key("+ + p q + + r s + t u"), alpha.key("+ u + t + s + r + q p"));
+        assert_ne!(alpha.key("- x x"), alpha.key("- x y"));
+        assert_ne!(ac_key("sin x"), ac_key("sin y"));
+
+        let constants = Canonicalization { alpha_rename: false, normalize_constants: true };
+        assert_eq!(constants.key("* 0.5 x"), constants.key("* 1/2 x"));
+        assert_eq!(constants.key("* 0.5 x"), constants.key("* / 1 2 x"));
+        assert_eq!(constants.key("* x / 2 4"), "(* 0.5 x)");
+        assert_ne!(constants.key("/ x 2"), constants.key("* 0.5 x"));
+        assert_ne!(ac_key("* 0.5 x"), ac_key("* / 1 2 x"));
+        assert_eq!(constants.key("* 1/0 x"), "(* 1/0 x)");
+        assert_eq!(constants.key("* 0/0 x"), "(* 0/0 x)");
+        assert_eq!(constants.key("* / 1 0 x"), "(* (/ 1 0) x)");
+        assert!(!constants.key("* / 1e300 1e-300 x").contains("inf"));
+        assert_ne!(constants.key("* 1/0 x"), constants.key("* 2/0 x"));
+
+        let equiv_exprs: HashSet<String> = ["sin x", "sin y", "* 0.5 x", "* / 1 2 y"]
+            .into_iter()
+            .map(|expr| expr.to_string())
+            .collect();
+        let both = Canonicalization { alpha_rename: true, normalize_constants: true };
+        assert_eq!(both.rm_dupes(&equiv_exprs).len(), 2);
+        assert_eq!(rm_ac_dupes(&equiv_exprs).len(), 4);
+    }
+}
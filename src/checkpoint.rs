@@ -0,0 +1,152 @@
+use crate::*;
+use std::fs::File;
+use std::io::{Read, Write};
+
+/// Checkpoint Offsets Struct
+/// offsets of the output file, statistics file and error log
+/// after a finished expression, 0 for a file that is not written
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CheckpointOffsets {
+    /// output file offset in bytes
+    pub output: u64,
+    /// statistics file offset in bytes
+    pub stats: u64,
+    /// error log offset in bytes
+    pub err: u64,
+}
+
+/// Checkpoint Struct
+/// manifest of a batch run, store the input line number and the
+/// file offsets of every finished expression, one
+/// `<line num> <output offset> <stats offset> <err offset>` record per line
+pub struct Checkpoint {
+    /// manifest file, opened in append mode
+    file: File,
+    /// finished input line number(s), start from 1
+    done: HashSet<usize>,
+    /// file offsets after the last finished expression
+    offsets: CheckpointOffsets,
+}
+
+impl Checkpoint {
+    /// ### constructor to create an empty manifest
+    /// ### an existing manifest will be truncated
+    /// #### Argument
+    /// * `filepath` - manifest filepath
+    /// #### Return
+    /// * `Result` - checkpoint, or io error
+    pub fn create(filepath: &str) -> std::io::Result<Self> {
+        let file = File::create(filepath)?;
+
+        return Ok(Checkpoint { file, done: Default::default(), offsets: Default::default() });
+    }
+
+    /// ### constructor to load an existing manifest and append to it
+    /// ### a missing manifest is created, an incomplete last record is ignored
+    /// #### Argument
+    /// * `filepath` - manifest filepath
+    /// #### Return
+    /// * `Result` - checkpoint, or io error
+    pub fn load(filepath: &str) -> std::io::Result<Self> {
+        let mut file = File::options().read(true).append(true).create(true).open(filepath)?;
+        let mut done: HashSet<usize> = Default::default();
+        let mut offsets = CheckpointOffsets::default();
+        let mut len: u64 = 0;
+
+        let mut content = String::new();
+        file.read_to_string(&mut content)?;
+        for record in content.split_inclusive('\n') {
+            if !record.ends_with('\n') { break; }
+            let fields: Vec<&str> = record.trim_end().split(' ').collect();
+            let parsed = match fields.as_slice() {
+                [line_num, output, stats, err] => {
+                    let offsets = (output.parse::<u64>(), stats.parse::<u64>(), err.parse::<u64>());
+                    match (line_num.parse::<usize>(), offsets) {
+                        (Ok(line_num), (Ok(output), Ok(stats), Ok(err))) => {
+                            Some((line_num, CheckpointOffsets { output, stats, err }))
+                        },
+                        _ => { None },
+                    }
+                },
+                _ => None,
+            };
+            match parsed {
+                Some((line_num, record_offsets)) => {
+                    /* records are written in input order, the last one is the furthest */
+                    done.insert(line_num);
+                    offsets = record_offsets;
+                    len += record.len() as u64;
+                },
+                None => { break; },
+            }
+        }
+
+        /* drop the incomplete record left by an interrupted run */
+        file.set_len(len)?;
+        file.flush()?;
+
+        return Ok(Checkpoint { file, done, offsets });
+    }
+
+    /// ### member function to check if an input line is finished
+    /// #### Arguments
+    /// * `self`
+    /// * `line_num` - input line number, start from 1
+    /// #### Return
+    /// * `bool` - whether the input line is finished or not
+    pub fn is_done(&self, line_num: usize) -> bool { return self.done.contains(&line_num); }
+
+    /// ### member function to get the file offsets
+    /// ### after the last finished expression
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `offsets` - file offsets in bytes
+    pub fn get_offsets(&self) -> CheckpointOffsets { return self.offsets; }
+
+    /// ### member function to record a finished input line
+    /// ### the record is synced to disk before returning
+    /// #### Arguments
+    /// * `self`
+    /// * `line_num` - input line number, start from 1
+    /// * `offsets` - file offsets after the expression block, synced to disk already
+    /// #### Return
+    /// * `Result` - empty, or io error
+    pub fn record(&mut self, line_num: usize, offsets: CheckpointOffsets) -> std::io::Result<()> {
+        writeln!(self.file, "{} {} {} {}", line_num, offsets.output, offsets.stats, offsets.err)?;
+        self.file.sync_data()?;
+        self.done.insert(line_num);
+        self.offsets = offsets;
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_ignores_incomplete_record() {
+        let filepath = std::env::temp_dir().join(format!("egen_ckpt_{}.ckpt", std::process::id()));
+        let filepath = filepath.to_str().unwrap();
+
+        let offsets = |output: u64, stats: u64, err: u64| { return CheckpointOffsets { output, stats, err }; };
+        let mut checkpoint = Checkpoint::create(filepath).unwrap();
+        checkpoint.record(1, offsets(10, 5, 0)).unwrap();
+        checkpoint.record(2, offsets(25, 12, 7)).unwrap();
+        drop(checkpoint);
+        File::options().append(true).open(filepath).unwrap().write_all(b"3 4").unwrap();
+
+        let mut checkpoint = Checkpoint::load(filepath).unwrap();
+        assert!(checkpoint.is_done(1) && checkpoint.is_done(2) && !checkpoint.is_done(3));
+        assert_eq!(checkpoint.get_offsets(), offsets(25, 12, 7));
+
+        checkpoint.record(3, offsets(40, 12, 20)).unwrap();
+        let checkpoint = Checkpoint::load(filepath).unwrap();
+        assert!(checkpoint.is_done(3));
+        assert_eq!(checkpoint.get_offsets(), offsets(40, 12, 20));
+
+        std::fs::remove_file(filepath).unwrap();
+    }
+}
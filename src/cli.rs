@@ -1,5 +1,6 @@
 use crate::*;
-use clap::{ArgAction, Parser};
+use clap::{ArgAction, Parser, ValueEnum};
+use serde::{Deserialize, Serialize};
 use std::process::exit;
 
 #[derive(Parser, Debug)]
@@ -11,6 +12,14 @@ use std::process::exit;
 )]
 /// Command line inputs
 pub struct Cli {
+    #[arg(
+        short = 'c',
+        long = "config",
+        required = false,
+    )]
+    /// TOML/JSON config filepath, overridden by the other flags
+    pub config_filepath: Option<String>,
+
     #[arg(
         short = 'f',
         long = "flag",
@@ -25,47 +34,276 @@ pub struct Cli {
         short = 'n',
         long = "n_equiv_exprs",
         required = false,
-        default_value_t = 10,
         value_parser = check_n_equiv_exprs
     )]
     /// number of equivalent expressions
-    pub n_equiv_exprs: u8,
+    pub n_equiv_exprs: Option<usize>,
 
     #[arg(
         short = 'l',
         long = "init_token_limit",
         required = false,
-        default_value_t = 8,
         value_parser = check_token_limit
     )]
     /// initial token limit
-    pub init_token_limit: u8,
+    pub init_token_limit: Option<u16>,
 
     #[arg(
         short = 'm',
         long = "max_token_limit",
         required = false,
-        default_value_t = 12,
         value_parser = check_token_limit
     )]
     /// maximum token limit
-    pub max_token_limit: u8,
+    pub max_token_limit: Option<u16>,
 
     #[arg(
         short = 't',
         long = "time_limit",
         required = false,
-        default_value_t = 350,
         value_parser = check_time_limit,
     )]
     /// initial time limit
-    pub init_time_limit: u16,
+    pub init_time_limit: Option<u32>,
+
+    #[arg(
+        long = "extraction",
+        required = false,
+        value_enum,
+    )]
+    /// extraction strategy
+    pub extraction: Option<ExtractionKind>,
+
+    #[arg(
+        long = "seed",
+        required = false,
+    )]
+    /// random seed of the production order and sampling extraction
+    pub seed: Option<u64>,
+
+    #[arg(
+        long = "deterministic",
+        required = false,
+        default_value_t = false,
+        action = ArgAction::SetTrue
+    )]
+    /// reproducible generation, bit-for-bit identical output files
+    pub deterministic: bool,
+
+    #[arg(
+        long = "cost",
+        required = false,
+        value_enum,
+    )]
+    /// cost function of top-k extraction
+    pub cost: Option<CostKind>,
+
+    #[arg(
+        long = "selection",
+        required = false,
+        value_enum,
+    )]
+    /// selection of the returned equivalent expressions
+    pub selection: Option<SelectionKind>,
+
+    #[arg(
+        long = "pool_factor",
+        required = false,
+    )]
+    /// candidate pool size as a multiple of the number of equivalent expressions
+    pub pool_factor: Option<usize>,
+
+    #[arg(
+        long = "early_stop",
+        required = false,
+        default_value_t = false,
+        action = ArgAction::SetTrue
+    )]
+    /// stop extracting once enough distinct expressions are found
+    pub early_stop: bool,
+
+    #[arg(
+        long = "oversample",
+        required = false,
+    )]
+    /// oversampling factor of the number of expressions for early termination
+    pub oversample: Option<f64>,
+
+    #[arg(
+        long = "escalation",
+        required = false,
+        value_enum,
+    )]
+    /// token/time limits escalation strategy
+    pub escalation: Option<EscalationKind>,
+
+    #[arg(
+        long = "token_step",
+        required = false,
+        value_parser = check_token_limit,
+    )]
+    /// token limit step of fixed escalation
+    pub token_step: Option<u16>,
+
+    #[arg(
+        long = "time_step",
+        required = false,
+    )]
+    /// time limit step of fixed escalation in sec
+    pub time_step: Option<u32>,
+
+    #[arg(
+        long = "token_factor",
+        required = false,
+        value_parser = check_factor,
+    )]
+    /// token limit factor of multiplicative escalation
+    pub token_factor: Option<f64>,
+
+    #[arg(
+        long = "time_factor",
+        required = false,
+        value_parser = check_factor,
+    )]
+    /// time limit factor of multiplicative escalation
+    pub time_factor: Option<f64>,
+
+    #[arg(
+        long = "time_budget",
+        required = false,
+        value_parser = check_time_budget,
+    )]
+    /// total time budget of an expression (saturation and extraction rounds) in sec
+    pub time_budget: Option<u64>,
+
+    #[arg(
+        long = "stop_no_progress",
+        required = false,
+        default_value_t = false,
+        action = ArgAction::SetTrue
+    )]
+    /// stop escalation when a round adds no new expression
+    pub stop_no_progress: bool,
+
+    #[arg(
+        long = "skip_unreachable",
+        required = false,
+        default_value_t = false,
+        action = ArgAction::SetTrue
+    )]
+    /// skip expressions that can never reach the number of equivalent expressions
+    pub skip_unreachable: bool,
+
+    #[arg(
+        long = "raise_token_limit",
+        required = false,
+        default_value_t = false,
+        action = ArgAction::SetTrue
+    )]
+    /// raise the initial token limit to the smallest limit with enough terms
+    pub raise_token_limit: bool,
+
+    #[arg(
+        long = "derivative_free",
+        required = false,
+        default_value_t = false,
+        action = ArgAction::SetTrue
+    )]
+    /// extract derivative-free expressions only
+    pub derivative_free: bool,
+
+    #[arg(
+        long = "alpha_rename",
+        required = false,
+        default_value_t = false,
+        action = ArgAction::SetTrue
+    )]
+    /// dedupe equivalent expressions modulo variable renaming
+    pub alpha_rename: bool,
+
+    #[arg(
+        long = "normalize_constants",
+        required = false,
+        default_value_t = false,
+        action = ArgAction::SetTrue
+    )]
+    /// dedupe equivalent expressions modulo numeric literal spelling
+    pub normalize_constants: bool,
+
+    #[arg(
+        long = "filter",
+        required = false,
+        action = ArgAction::Append,
+    )]
+    /// filters of the productions, sentential forms and expressions
+    pub filter: Vec<String>,
+
+    #[arg(
+        long = "iter_limit",
+        required = false,
+    )]
+    /// iteration limit of equality saturation
+    pub iter_limit: Option<usize>,
+
+    #[arg(
+        long = "node_limit",
+        required = false,
+    )]
+    /// e-node limit of equality saturation
+    pub node_limit: Option<usize>,
+
+    #[arg(
+        long = "sat_time_limit",
+        required = false,
+        value_parser = check_sat_time_limit,
+    )]
+    /// time limit of equality saturation in sec
+    pub sat_time_limit: Option<f64>,
+
+    #[arg(
+        long = "scheduler",
+        required = false,
+        value_enum,
+    )]
+    /// rewrite scheduler of equality saturation
+    pub scheduler: Option<SchedulerKind>,
+
+    #[arg(
+        long = "match_limit",
+        required = false,
+    )]
+    /// initial match limit of every rule for backoff scheduler
+    pub match_limit: Option<usize>,
+
+    #[arg(
+        long = "ban_length",
+        required = false,
+    )]
+    /// initial ban length of every rule for backoff scheduler
+    pub ban_length: Option<usize>,
+
+    #[arg(
+        long = "rule_match_limit",
+        required = false,
+        action = ArgAction::Append,
+        value_parser = check_rule_limit,
+    )]
+    /// per-rule initial match limit <rule>=<limit> for backoff scheduler
+    pub rule_match_limits: Vec<(String, usize)>,
+
+    #[arg(
+        long = "rule_ban_length",
+        required = false,
+        action = ArgAction::Append,
+        value_parser = check_rule_limit,
+    )]
+    /// per-rule initial ban length <rule>=<length> for backoff scheduler
+    pub rule_ban_lengths: Vec<(String, usize)>,
 
     #[arg(
         short = 'e',
         long = "input_expr",
         required = false,
-        required_unless_present_all = &["input_filepath", "output_filepath"],
         conflicts_with_all = &["input_filepath", "output_filepath"]
     )]
     /// input expression
@@ -75,8 +313,6 @@ pub struct Cli {
         short = 'i',
         long = "input_filepath",
         required = false,
-        required_unless_present = "input_expr",
-        requires = "output_filepath",
         conflicts_with = "input_expr"
     )]
     /// input filepath
@@ -86,65 +322,213 @@ pub struct Cli {
         short = 'o',
         long = "output_filepath",
         required = false,
-        required_unless_present = "input_expr",
-        requires = "input_filepath",
         conflicts_with = "input_expr",
     )]
     /// output filepath
     pub output_filepath: Option<String>,
+
+    #[arg(
+        short = 'j',
+        long = "jobs",
+        required = false,
+        value_parser = check_jobs,
+        conflicts_with = "input_expr",
+    )]
+    /// number of parallel jobs
+    pub jobs: Option<u16>,
+
+    #[arg(
+        short = 'r',
+        long = "resume",
+        required = false,
+        default_value_t = false,
+        action = ArgAction::SetTrue,
+        conflicts_with = "input_expr",
+    )]
+    /// resume from the checkpoint manifest of the output file
+    pub resume: bool,
+
+    #[arg(
+        short = 'x',
+        long = "format",
+        required = false,
+        value_enum,
+        conflicts_with = "input_expr",
+    )]
+    /// output file format
+    pub format: Option<OutputFormat>,
+
+    #[arg(
+        short = 's',
+        long = "stats_filepath",
+        required = false,
+        conflicts_with = "input_expr",
+    )]
+    /// per-expression statistics filepath
+    pub stats_filepath: Option<String>,
 }
 
-#[derive(Clone, Debug)]
-/// Argument datatype struct
-pub enum CliDtype {
-    /// boolean
-    Bool(bool),
-    /// unsigned integer 8-bit
-    UInt8(u8),
-    /// unsigned integer 16-bit
-    UInt16(u16),
-    /// floating point 64-bit
-    Float(f64),
-    /// string
-    String(String),
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// Extraction strategy
+pub enum ExtractionKind {
+    /// depth-first search, optimized or exhaustive by the optimized extraction flag
+    Dfs,
+    /// all expressions of k tokens before any expression of k+1 tokens
+    Shortest,
+    /// uniform random sample of all expressions within the token limit
+    Sample,
+    /// k cheapest expressions under a cost function
+    #[value(name = "topk")]
+    TopK,
+    /// shortest first from memoized terms of every eclass, built bottom-up
+    #[value(name = "bottomup")]
+    BottomUp,
 }
 
-impl CliDtype {
-    /// public function to convert member variables
-    /// in struct ArgDtype to type String
-    /// ## Argument
+impl ExtractionKind {
+    /// ### public function to convert ExtractionKind to its command line name
+    /// #### Argument
     /// * `self`
-    /// ## Return
-    /// * `String`
-    pub fn to_string(&self) -> String {
+    /// #### Return
+    /// * `&str` - command line name
+    pub fn as_str(&self) -> &'static str {
         match self {
-            CliDtype::Bool(value) => value.to_string(),
-            CliDtype::UInt8(value) => value.to_string(),
-            CliDtype::UInt16(value) => value.to_string(),
-            CliDtype::Float(value) => value.to_string(),
-            CliDtype::String(value) => value.clone(),
+            ExtractionKind::Dfs => "dfs",
+            ExtractionKind::Shortest => "shortest",
+            ExtractionKind::Sample => "sample",
+            ExtractionKind::TopK => "topk",
+            ExtractionKind::BottomUp => "bottomup",
         }
     }
+}
 
-    /// ### public function to convert String variables to type ArgDtype
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// Cost function of top-k extraction
+pub enum CostKind {
+    /// AST size
+    Size,
+    /// sum of operator weights
+    Operator,
+    /// # of trig functions, then AST size
+    Trig,
+    /// AST size plus a penalty per level of AST depth
+    Depth,
+}
+
+impl CostKind {
+    /// ### public function to convert CostKind to its command line name
     /// #### Argument
-    /// * `s` - &str type variable
+    /// * `self`
     /// #### Return
-    /// * `Result` - whether conversion is successfully or not
-    pub fn from_string(s: &str) -> Result<Self, &'static str> {
-        if let Ok(value) = s.parse::<bool>() {
-            return Ok(CliDtype::Bool(value));
+    /// * `&str` - command line name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CostKind::Size => "size",
+            CostKind::Operator => "operator",
+            CostKind::Trig => "trig",
+            CostKind::Depth => "depth",
         }
-        if let Ok(value) = s.parse::<u8>() {
-            return Ok(CliDtype::UInt8(value));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// Token/time limits escalation strategy
+pub enum EscalationKind {
+    /// increase limits by fixed steps
+    Fixed,
+    /// multiply limits by fixed factors
+    Multiplicative,
+}
+
+impl EscalationKind {
+    /// ### public function to convert EscalationKind to its command line name
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `&str` - command line name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EscalationKind::Fixed => "fixed",
+            EscalationKind::Multiplicative => "multiplicative",
         }
-        if let Ok(value) = s.parse::<u16>() {
-            return Ok(CliDtype::UInt16(value));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// Selection of the returned equivalent expressions
+pub enum SelectionKind {
+    /// all expressions found
+    All,
+    /// most diverse expressions by edit distance in prefix notation
+    Edit,
+    /// most diverse expressions by operator set (Jaccard) distance
+    Operator,
+}
+
+impl SelectionKind {
+    /// ### public function to convert SelectionKind to its command line name
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `&str` - command line name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SelectionKind::All => "all",
+            SelectionKind::Edit => "edit",
+            SelectionKind::Operator => "operator",
         }
-        if let Ok(value) = s.parse::<f64>() {
-            return Ok(CliDtype::Float(value));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// Rewrite scheduler of equality saturation
+pub enum SchedulerKind {
+    /// run every rewrite rule every iteration
+    Simple,
+    /// ban explosive rewrite rules with exponential backoff
+    Backoff,
+}
+
+impl SchedulerKind {
+    /// ### public function to convert SchedulerKind to its command line name
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `&str` - command line name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SchedulerKind::Simple => "simple",
+            SchedulerKind::Backoff => "backoff",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// Output file format
+pub enum OutputFormat {
+    /// input expression without parentheses, 1 equivalent expression per line, blank line
+    Text,
+    /// 1 JSON object per input expression
+    Jsonl,
+}
+
+impl OutputFormat {
+    /// ### public function to convert OutputFormat to its command line name
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `&str` - command line name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Jsonl => "jsonl",
         }
-        Ok(CliDtype::String(s.to_string()))
     }
 }
 
@@ -153,12 +537,18 @@ impl CliDtype {
 /// #### Argument
 /// * `s` - user's input
 /// #### Return
-/// * `Result` valid u8 input, or error message
-fn check_n_equiv_exprs(s: &str) -> Result<u8, String> {
-    match s.parse::<u8>() {
-        Ok(n_equiv_exprs) => { return Ok(n_equiv_exprs); },
+/// * `Result` valid usize input, or error message
+fn check_n_equiv_exprs(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(n_equiv_exprs) => {
+            if n_equiv_exprs > 0 {
+                return Ok(n_equiv_exprs);
+            } else {
+                return Err(format!("\n[ERROR]: Invalid input value '{}' for number of equivalent expressions, expect usize > 0.", s));
+            }
+        },
         Err(_) => {
-            return Err(format!("\n[ERROR]: Invalid value '{}' for number of equivalent expressions, expect u8.", s));
+            return Err(format!("\n[ERROR]: Invalid value '{}' for number of equivalent expressions, expect usize.", s));
         },
     };
 }
@@ -168,18 +558,18 @@ fn check_n_equiv_exprs(s: &str) -> Result<u8, String> {
 /// #### Argument
 /// * `s` - user's input
 /// #### Return
-/// * `Result` valid u8 input, or error message
-fn check_token_limit(s: &str) -> Result<u8, String> {
-    match s.parse::<u8>() {
+/// * `Result` valid u16 input, or error message
+fn check_token_limit(s: &str) -> Result<u16, String> {
+    match s.parse::<u16>() {
         Ok(init_token_limit) => {
-            if init_token_limit > 0 && init_token_limit <= u8::MAX {
+            if init_token_limit > 0 {
                 return Ok(init_token_limit);
             } else {
-                return Err(format!("\n[ERROR]: Invalid input value '{}' for token limit, expect u8 in range (0, 2^8].", s));
+                return Err(format!("\n[ERROR]: Invalid input value '{}' for token limit, expect u16 in range (0, 2^16).", s));
             }
         },
         Err(_) => {
-            return Err(format!("\n[ERROR]: Invalid value '{}' for token limit, expect u8.", s));
+            return Err(format!("\n[ERROR]: Invalid value '{}' for token limit, expect u16.", s));
         },
     };
 }
@@ -189,18 +579,124 @@ fn check_token_limit(s: &str) -> Result<u8, String> {
 /// #### Argument
 /// * `s` - user's input
 /// #### Return
-/// * `Result` valid u16 input, or error message
-fn check_time_limit(s: &str) -> Result<u16, String> {
-    match s.parse::<u16>() {
+/// * `Result` valid u32 input, or error message
+fn check_time_limit(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(time_limit) => {
+            if time_limit > 0 {
+                return Ok(time_limit);
+            } else {
+                return Err(format!("\n[ERROR]: Invalid input value '{}' for time limit, expect u32 in range (0, 2^32).", s));
+            }
+        },
+        Err(_) => {
+            return Err(format!("\n[ERROR]: Invalid value '{}' for time limit, expect u32.", s));
+        },
+    };
+}
+
+/// ### private function to check if user's input for escalation factor
+/// ### token_factor or time_factor is valid
+/// #### Argument
+/// * `s` - user's input
+/// #### Return
+/// * `Result` valid f64 input, or error message
+fn check_factor(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(factor) => {
+            if factor >= 1.0 {
+                return Ok(factor);
+            } else {
+                return Err(format!("\n[ERROR]: Invalid input value '{}' for escalation factor, expect f64 ≥ 1.", s));
+            }
+        },
+        Err(_) => {
+            return Err(format!("\n[ERROR]: Invalid value '{}' for escalation factor, expect f64.", s));
+        },
+    };
+}
+
+/// ### private function to check if user's input for time budget variable
+/// ### time_budget is valid
+/// #### Argument
+/// * `s` - user's input
+/// #### Return
+/// * `Result` valid u64 input, or error message
+fn check_time_budget(s: &str) -> Result<u64, String> {
+    match s.parse::<u64>() {
+        Ok(time_budget) => {
+            if time_budget > 0 {
+                return Ok(time_budget);
+            } else {
+                return Err(format!("\n[ERROR]: Invalid input value '{}' for time budget, expect u64 > 0.", s));
+            }
+        },
+        Err(_) => {
+            return Err(format!("\n[ERROR]: Invalid value '{}' for time budget, expect u64.", s));
+        },
+    };
+}
+
+/// ### private function to check if user's input for saturation time limit
+/// ### variable sat_time_limit is valid
+/// #### Argument
+/// * `s` - user's input
+/// #### Return
+/// * `Result` valid f64 input, or error message
+fn check_sat_time_limit(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
         Ok(time_limit) => {
-            if time_limit > 0 && time_limit <= u16::MAX {
+            if time_limit > 0.0 && time_limit.is_finite() {
                 return Ok(time_limit);
             } else {
-                return Err(format!("\n[ERROR]: Invalid input value '{}' for time limit, expect to u16 in range (0, 2^16].", s));
+                return Err(format!("\n[ERROR]: Invalid input value '{}' for saturation time limit, expect f64 > 0.", s));
+            }
+        },
+        Err(_) => {
+            return Err(format!("\n[ERROR]: Invalid value '{}' for saturation time limit, expect f64.", s));
+        },
+    };
+}
+
+/// ### private function to check if user's input for per-rule limit
+/// ### <rule>=<limit> is valid, rule names may contain '='
+/// #### Argument
+/// * `s` - user's input
+/// #### Return
+/// * `Result` valid (rule name, usize) input, or error message
+fn check_rule_limit(s: &str) -> Result<(String, usize), String> {
+    match s.rsplit_once('=') {
+        Some((rule, limit)) if !rule.is_empty() => {
+            match limit.parse::<usize>() {
+                Ok(limit) => { return Ok((rule.to_string(), limit)); },
+                Err(_) => {
+                    return Err(format!("\n[ERROR]: Invalid limit '{}' for rule '{}', expect usize.", limit, rule));
+                },
+            }
+        },
+        _ => {
+            return Err(format!("\n[ERROR]: Invalid value '{}' for per-rule limit, expect <rule>=<limit>.", s));
+        },
+    };
+}
+
+/// ### private function to check if user's input for number of parallel jobs
+/// ### variable jobs is valid
+/// #### Argument
+/// * `s` - user's input
+/// #### Return
+/// * `Result` valid u16 input, or error message
+fn check_jobs(s: &str) -> Result<u16, String> {
+    match s.parse::<u16>() {
+        Ok(jobs) => {
+            if jobs > 0 {
+                return Ok(jobs);
+            } else {
+                return Err(format!("\n[ERROR]: Invalid input value '{}' for number of jobs, expect u16 in range (0, 2^16).", s));
             }
         },
         Err(_) => {
-            return Err(format!("\n[ERROR]: Invalid value '{}' for time limit, expect u16.", s));
+            return Err(format!("\n[ERROR]: Invalid value '{}' for number of jobs, expect u16.", s));
         },
     };
 }
@@ -211,11 +707,37 @@ fn check_time_limit(s: &str) -> Result<u16, String> {
 /// #### Return
 /// * `None`
 pub fn help() {
-    log_info_raw("[USAGE]: cargo run [-f] <optim ext flag>   [-n] <n equiv exprs>\n");
+    log_info_raw("[USAGE]: cargo run [-c] <config filepath>\n");
+    log_info_raw("[USAGE]:           [-f] <optim ext flag>   [-n] <n equiv exprs>\n");
     log_info_raw("[USAGE]:           [-l] <init token limit> [-m] <max token limit>\n");
     log_info_raw("[USAGE]:           [-t] <init time limit>  [-e] <expr>\n");
+    log_info_raw("[USAGE]:           [--extraction] <extraction strategy> [--seed] <seed>\n");
+    log_info_raw("[USAGE]:           [--cost] <cost function>        [--deterministic]\n");
+    log_info_raw("[USAGE]:           [--selection] <selection>       [--pool_factor] <pool factor>\n");
+    log_info_raw("[USAGE]:           [--oversample] <oversample>     [--early_stop]\n");
+    log_info_raw("[USAGE]:           [--escalation] <escalation strategy>\n");
+    log_info_raw("[USAGE]:           [--token_step] <token step>     [--time_step] <time step>\n");
+    log_info_raw("[USAGE]:           [--token_factor] <token factor> [--time_factor] <time factor>\n");
+    log_info_raw("[USAGE]:           [--time_budget] <time budget>   [--stop_no_progress]\n");
+    log_info_raw("[USAGE]:           [--skip_unreachable]            [--raise_token_limit]\n");
+    log_info_raw("[USAGE]:           [--derivative_free]\n");
+    log_info_raw("[USAGE]:           [--filter] <filter> ...\n");
+    log_info_raw("[USAGE]:           [--alpha_rename]                [--normalize_constants]\n");
+    log_info_raw("[USAGE]:           [--iter_limit] <iter limit>     [--node_limit] <node limit>\n");
+    log_info_raw("[USAGE]:           [--sat_time_limit] <sat time limit>\n");
+    log_info_raw("[USAGE]:           [--scheduler] <scheduler>\n");
+    log_info_raw("[USAGE]:           [--match_limit] <match limit>   [--ban_length] <ban length>\n");
+    log_info_raw("[USAGE]:           [--rule_match_limit] <rule>=<limit> ...\n");
+    log_info_raw("[USAGE]:           [--rule_ban_length] <rule>=<length> ...\n");
     log_info_raw("[USAGE]:           [-i] <input filepath> & [-o] <output filepath>\n");
+    log_info_raw("[USAGE]:           [-j] <jobs>             [-r] <resume flag>\n");
+    log_info_raw("[USAGE]:           [-x] <output format>    [-s] <stats filepath>\n");
     log_info_raw("[USAGE]:\n");
+    log_info_raw("[USAGE]: <config filepath>  -> TOML/JSON generation config filepath\n");
+    log_info_raw("[USAGE]:  fields            -> GenerationConfig fields, e.g. n_equiv_exprs = 20\n");
+    log_info_raw("[USAGE]:  override          -> flags provided on the command line\n");
+    log_info_raw("[USAGE]:  default            = None\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
     log_info_raw("[USAGE]: <optimized flag>   -> optimized extraction flag\n");
     log_info_raw("[USAGE]:  true              -> run optimized extraction\n");
     log_info_raw("[USAGE]:  false             -> run exhaustive extraction\n");
@@ -223,21 +745,131 @@ pub fn help() {
     log_info_raw("[USAGE]:  default            = false\n");
     log_info_raw("[USAGE]:  required          -> false\n");
     log_info_raw("[USAGE]: <n equiv exprs>    -> number of equivalent expressions\n");
-    log_info_raw("[USAGE]:  datatype          -> uint8\n");
+    log_info_raw("[USAGE]:  datatype          -> usize\n");
     log_info_raw("[USAGE]:  default            = 10\n");
     log_info_raw("[USAGE]:  required          -> false\n");
     log_info_raw("[USAGE]: <init token limit> -> initial tokens limit\n");
-    log_info_raw("[USAGE]:  datatype          -> uint8\n");
+    log_info_raw("[USAGE]:  datatype          -> uint16\n");
     log_info_raw("[USAGE]:  default            = 8\n");
     log_info_raw("[USAGE]:  required          -> false\n");
     log_info_raw("[USAGE]: <max token limit>  -> maximum tokens limit\n");
-    log_info_raw("[USAGE]:  datatype          -> uint8\n");
+    log_info_raw("[USAGE]:  datatype          -> uint16\n");
     log_info_raw("[USAGE]:  default            = 12\n");
     log_info_raw("[USAGE]:  required          -> false\n");
     log_info_raw("[USAGE]: <init time limit>  -> initial time limit in sec\n");
-    log_info_raw("[USAGE]:  datatype          -> uint16\n");
+    log_info_raw("[USAGE]:  datatype          -> uint32\n");
     log_info_raw("[USAGE]:  default            = 350\n");
     log_info_raw("[USAGE]:  required          -> false\n");
+    log_info_raw("[USAGE]: <extraction strategy> -> order of enumerating equivalent expressions\n");
+    log_info_raw("[USAGE]:  dfs               -> depth-first, <optim ext flag> applies\n");
+    log_info_raw("[USAGE]:  shortest          -> all exprs of k tokens before any of k+1 tokens\n");
+    log_info_raw("[USAGE]:  sample            -> uniform random sample of exprs within token limit\n");
+    log_info_raw("[USAGE]:  topk              -> <n equiv exprs> cheapest exprs by <cost function>\n");
+    log_info_raw("[USAGE]:  bottomup          -> shortest first, subterms of every eclass built once\n");
+    log_info_raw("[USAGE]:  datatype          -> String\n");
+    log_info_raw("[USAGE]:  default            = dfs\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
+    log_info_raw("[USAGE]: <seed>             -> random seed of the production order and sample\n");
+    log_info_raw("[USAGE]:                       extraction, 0 for the canonical production order\n");
+    log_info_raw("[USAGE]:  datatype          -> uint64\n");
+    log_info_raw("[USAGE]:  default            = 0\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
+    log_info_raw("[USAGE]: <deterministic>    -> reproducible generation, symbols are interned in\n");
+    log_info_raw("[USAGE]:                       input order and jsonl records have no times\n");
+    log_info_raw("[USAGE]:  datatype          -> bool\n");
+    log_info_raw("[USAGE]:  default            = true with the deterministic cargo feature\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
+    log_info_raw("[USAGE]: <cost function>    -> cost function of topk extraction\n");
+    log_info_raw("[USAGE]:  size              -> AST size\n");
+    log_info_raw("[USAGE]:  operator          -> sum of operator weights\n");
+    log_info_raw("[USAGE]:  trig              -> # of trig functions, then AST size\n");
+    log_info_raw("[USAGE]:  depth             -> AST size + 0.5 * AST depth\n");
+    log_info_raw("[USAGE]:  datatype          -> String\n");
+    log_info_raw("[USAGE]:  default            = size\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
+    log_info_raw("[USAGE]: <selection>        -> selection of the returned equivalent expressions\n");
+    log_info_raw("[USAGE]:  all               -> all expressions found\n");
+    log_info_raw("[USAGE]:  edit              -> most diverse by edit distance in prefix notation\n");
+    log_info_raw("[USAGE]:  operator          -> most diverse by operator set distance\n");
+    log_info_raw("[USAGE]:  datatype          -> String\n");
+    log_info_raw("[USAGE]:  default            = all\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
+    log_info_raw("[USAGE]: <pool factor>      -> candidate pool of <pool factor> * <n equiv exprs>\n");
+    log_info_raw("[USAGE]:                       expressions to select from\n");
+    log_info_raw("[USAGE]:  datatype          -> usize\n");
+    log_info_raw("[USAGE]:  default            = 1\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
+    log_info_raw("[USAGE]: <oversample>       -> stop extraction at <oversample> * <n equiv exprs>\n");
+    log_info_raw("[USAGE]:                       distinct expressions, implies --early_stop\n");
+    log_info_raw("[USAGE]:  datatype          -> f64\n");
+    log_info_raw("[USAGE]:  default            = 1.0\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
+    log_info_raw("[USAGE]: <early_stop>       -> stop extraction once enough distinct expressions\n");
+    log_info_raw("[USAGE]:                       are found instead of at the time limit\n");
+    log_info_raw("[USAGE]:  datatype          -> bool\n");
+    log_info_raw("[USAGE]:  default            = false\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
+    log_info_raw("[USAGE]: <escalation strategy> -> token/time limits escalation between rounds\n");
+    log_info_raw("[USAGE]:  fixed             -> + <token step> tokens, + <time step> sec\n");
+    log_info_raw("[USAGE]:  multiplicative    -> * <token factor> tokens, * <time factor> sec\n");
+    log_info_raw("[USAGE]:  datatype          -> String\n");
+    log_info_raw("[USAGE]:  default            = fixed\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
+    log_info_raw("[USAGE]: <token step>       -> token limit step, default = 1\n");
+    log_info_raw("[USAGE]: <time step>        -> time limit step in sec, default = 300\n");
+    log_info_raw("[USAGE]: <token factor>     -> token limit factor, default = 1.5\n");
+    log_info_raw("[USAGE]: <time factor>      -> time limit factor, default = 2.0\n");
+    log_info_raw("[USAGE]: <time budget>      -> total time budget of an expression in sec,\n");
+    log_info_raw("[USAGE]:                       saturation and all extraction rounds\n");
+    log_info_raw("[USAGE]:  datatype          -> uint64\n");
+    log_info_raw("[USAGE]:  default            = None\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
+    log_info_raw("[USAGE]: <stop_no_progress> -> stop when a round adds no new expression\n");
+    log_info_raw("[USAGE]:  datatype          -> bool\n");
+    log_info_raw("[USAGE]:  default            = false\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
+    log_info_raw("[USAGE]: <skip_unreachable> -> skip exprs with fewer terms than <n equiv exprs>\n");
+    log_info_raw("[USAGE]:                       within <max token limit>\n");
+    log_info_raw("[USAGE]:  datatype          -> bool\n");
+    log_info_raw("[USAGE]:  default            = false\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
+    log_info_raw("[USAGE]: <raise_token_limit> -> raise <init token limit> to the smallest limit\n");
+    log_info_raw("[USAGE]:                       with at least <n equiv exprs> terms\n");
+    log_info_raw("[USAGE]:  datatype          -> bool\n");
+    log_info_raw("[USAGE]:  default            = false\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
+    log_info_raw("[USAGE]: <derivative_free>  -> extract exprs without unevaluated d and i only\n");
+    log_info_raw("[USAGE]:  datatype          -> bool\n");
+    log_info_raw("[USAGE]:  default            = false\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
+    log_info_raw("[USAGE]: <alpha_rename>     -> dedupe exprs modulo variable renaming, e.g. sin x, sin y\n");
+    log_info_raw("[USAGE]:  datatype          -> bool\n");
+    log_info_raw("[USAGE]:  default            = false\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
+    log_info_raw("[USAGE]: <normalize_constants> -> dedupe exprs modulo literal spelling, e.g. 0.5, 1/2, / 1 2\n");
+    log_info_raw("[USAGE]:  datatype          -> bool\n");
+    log_info_raw("[USAGE]:  default            = false\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
+    log_info_raw("[USAGE]: <filter>           -> prune degenerate forms, repeatable, replaces defaults\n");
+    log_info_raw("[USAGE]:  identity          -> * 1, pow 1, / 1, + 0, - 0 productions\n");
+    log_info_raw("[USAGE]:  derivative_free   -> d and i productions\n");
+    log_info_raw("[USAGE]:  op:<op>           -> exprs with operator <op>, e.g. op:d\n");
+    log_info_raw("[USAGE]:  pattern:<pattern> -> exprs matching <pattern>, e.g. 'pattern:(pow ?a 1)'\n");
+    log_info_raw("[USAGE]:  depth:<max depth> -> exprs deeper than <max depth>\n");
+    log_info_raw("[USAGE]:  datatype          -> String\n");
+    log_info_raw("[USAGE]:  default            = identity\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
+    log_info_raw("[USAGE]: <iter limit>       -> iteration limit of equality saturation, default = 30\n");
+    log_info_raw("[USAGE]: <node limit>       -> e-node limit of equality saturation, default = 10000\n");
+    log_info_raw("[USAGE]: <sat time limit>   -> time limit of equality saturation in sec, default = 5\n");
+    log_info_raw("[USAGE]: <scheduler>        -> rewrite scheduler of equality saturation\n");
+    log_info_raw("[USAGE]:  simple            -> run every rule every iteration\n");
+    log_info_raw("[USAGE]:  backoff           -> ban rules exceeding their match limits\n");
+    log_info_raw("[USAGE]:  default            = backoff\n");
+    log_info_raw("[USAGE]: <match limit>      -> initial match limit of every rule, default = 1000\n");
+    log_info_raw("[USAGE]: <ban length>       -> initial ban length of every rule, default = 5\n");
+    log_info_raw("[USAGE]: <rule>=<limit>     -> initial match limit of 1 rule, repeatable\n");
+    log_info_raw("[USAGE]: <rule>=<length>    -> initial ban length of 1 rule, repeatable\n");
     log_info_raw("[USAGE]: <expr>             -> initial expression\n");
     log_info_raw("[USAGE]:  datatype          -> String\n");
     log_info_raw("[USAGE]:  default            = None\n");
@@ -250,14 +882,99 @@ pub fn help() {
     log_info_raw("[USAGE]:  type              -> String\n");
     log_info_raw("[USAGE]:  default            = None\n");
     log_info_raw("[USAGE]:  required          -> True if [-e] not provided\n");
+    log_info_raw("[USAGE]: <jobs>             -> number of expressions generated in parallel\n");
+    log_info_raw("[USAGE]:  datatype          -> uint16\n");
+    log_info_raw("[USAGE]:  default            = 1\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
+    log_info_raw("[USAGE]: <resume flag>      -> skip expressions finished in <output filepath>.ckpt\n");
+    log_info_raw("[USAGE]:  datatype          -> bool\n");
+    log_info_raw("[USAGE]:  default            = false\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
+    log_info_raw("[USAGE]: <output format>    -> output file format\n");
+    log_info_raw("[USAGE]:  text              -> input expr, 1 equiv expr per line, blank line\n");
+    log_info_raw("[USAGE]:  jsonl             -> 1 JSON object per input expr\n");
+    log_info_raw("[USAGE]:  datatype          -> String\n");
+    log_info_raw("[USAGE]:  default            = text\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
+    log_info_raw("[USAGE]: <stats filepath>   -> per-expression statistics JSONL filepath\n");
+    log_info_raw("[USAGE]:  type              -> String\n");
+    log_info_raw("[USAGE]:  default            = None\n");
+    log_info_raw("[USAGE]:  required          -> false\n");
+}
+
+/// ### private function to override config settings
+/// ### with the command line flags that are provided
+/// #### Arguments
+/// * `cli` - command line inputs
+/// * `config` - config to override
+/// #### Return
+/// * `None`
+fn apply_args(cli: Cli, config: &mut GenerationConfig) {
+    if cli.flag { config.optimized = true; }
+    if let Some(n_equiv_exprs) = cli.n_equiv_exprs { config.n_equiv_exprs = n_equiv_exprs; }
+    if let Some(init_token_limit) = cli.init_token_limit { config.init_token_limit = init_token_limit; }
+    if let Some(max_token_limit) = cli.max_token_limit { config.max_token_limit = max_token_limit; }
+    if let Some(init_time_limit) = cli.init_time_limit { config.init_time_limit = init_time_limit; }
+    if let Some(extraction) = cli.extraction { config.extraction = extraction; }
+    if let Some(seed) = cli.seed { config.seed = seed; }
+    if cli.deterministic { config.deterministic = true; }
+    if let Some(cost) = cli.cost { config.cost = cost; }
+    if let Some(selection) = cli.selection { config.selection = selection; }
+    if let Some(pool_factor) = cli.pool_factor { config.pool_factor = pool_factor; }
+    if cli.early_stop { config.early_stop = true; }
+    if let Some(oversample) = cli.oversample {
+        config.early_stop = true;
+        config.oversample = oversample;
+    }
+    if let Some(escalation) = cli.escalation { config.escalation = escalation; }
+    if let Some(token_step) = cli.token_step { config.token_step = token_step; }
+    if let Some(time_step) = cli.time_step { config.time_step = time_step; }
+    if let Some(token_factor) = cli.token_factor { config.token_factor = token_factor; }
+    if let Some(time_factor) = cli.time_factor { config.time_factor = time_factor; }
+    if let Some(time_budget) = cli.time_budget { config.time_budget = Some(time_budget); }
+    if cli.stop_no_progress { config.stop_no_progress = true; }
+    if cli.skip_unreachable { config.skip_unreachable = true; }
+    if cli.raise_token_limit { config.raise_token_limit = true; }
+    if cli.derivative_free { config.derivative_free = true; }
+    if cli.alpha_rename { config.alpha_rename = true; }
+    if cli.normalize_constants { config.normalize_constants = true; }
+    if !cli.filter.is_empty() { config.filters = cli.filter; }
+    if let Some(iter_limit) = cli.iter_limit { config.iter_limit = iter_limit; }
+    if let Some(node_limit) = cli.node_limit { config.node_limit = node_limit; }
+    if let Some(sat_time_limit) = cli.sat_time_limit { config.sat_time_limit = sat_time_limit; }
+    if let Some(scheduler) = cli.scheduler { config.scheduler = scheduler; }
+    if let Some(match_limit) = cli.match_limit { config.match_limit = match_limit; }
+    if let Some(ban_length) = cli.ban_length { config.ban_length = ban_length; }
+    config.rule_match_limits.extend(cli.rule_match_limits);
+    config.rule_ban_lengths.extend(cli.rule_ban_lengths);
+
+    /* an input expression replaces the input/output filepaths of the config file, and vice versa */
+    if cli.input_expr.is_some() {
+        config.input_expr = cli.input_expr;
+        config.input_filepath = None;
+        config.output_filepath = None;
+    }
+    if cli.input_filepath.is_some() || cli.output_filepath.is_some() {
+        config.input_expr = None;
+    }
+    if let Some(input_filepath) = cli.input_filepath { config.input_filepath = Some(input_filepath); }
+    if let Some(output_filepath) = cli.output_filepath { config.output_filepath = Some(output_filepath); }
+    if let Some(jobs) = cli.jobs { config.jobs = jobs; }
+    if cli.resume { config.resume = true; }
+    if let Some(format) = cli.format { config.format = format; }
+    if let Some(stats_filepath) = cli.stats_filepath { config.stats_filepath = Some(stats_filepath); }
+
+    return;
 }
 
 /// ### public function to parse command line input(s)
+/// ### into a generation config, starting from the config file
+/// ### if provided, and validate it
 /// #### Argument
 /// * `None`
 /// #### Return
-/// * `None` - command line input(s)
-pub fn parse_args() -> Vec<CliDtype> {
+/// * `GenerationConfig` - validated generation config
+pub fn parse_args() -> GenerationConfig {
     let cli: Cli = match Cli::try_parse() {
         Ok(cli) => { cli },
         Err(e) => {
@@ -268,32 +985,65 @@ pub fn parse_args() -> Vec<CliDtype> {
         },
     };
 
-    if cli.max_token_limit < cli.init_token_limit {
-        log_error(&format!("Maximum token limit {} needs to be ≥ initial token limit {}.\n", cli.max_token_limit, cli.init_token_limit));
-        exit(1);
+    let mut config = match &cli.config_filepath {
+        Some(config_filepath) => {
+            match GenerationConfig::from_file(config_filepath) {
+                Ok(config) => { config },
+                Err(e) => {
+                    log_error(&format!("{}\n", e));
+                    exit(1);
+                },
+            }
+        },
+        None => { GenerationConfig::default() },
+    };
+    apply_args(cli, &mut config);
+
+    match config.validate() {
+        Ok(_) => {},
+        Err(e) => {
+            log_error(&format!("{}\n", e));
+            help();
+            exit(1);
+        },
     }
 
-    let mut cli_dtype: Vec<CliDtype> = vec![CliDtype::Bool(cli.flag),
-                                            CliDtype::UInt8(cli.n_equiv_exprs),
-                                            CliDtype::UInt8(cli.init_token_limit),
-                                            CliDtype::UInt8(cli.max_token_limit),
-                                            CliDtype::UInt16(cli.init_time_limit),];
+    return config;
+}
 
-    match cli.input_expr {
-        Some(input_expr) => {
-            cli_dtype.push(CliDtype::String(input_expr));
-            return cli_dtype;
-        },
-        None => { },
-    };
-    match cli.input_filepath {
-        Some(input_fpath) => { cli_dtype.push(CliDtype::String(input_fpath)); },
-        None => { },
-    };
-    match cli.output_filepath {
-        Some(output_fpath) => { cli_dtype.push(CliDtype::String(output_fpath)); },
-        None => { },
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saturation_args() {
+        let cli = Cli::parse_from([
+            "egen", "--iter_limit", "2", "--node_limit", "50000", "--sat_time_limit", "30",
+            "--scheduler", "backoff", "--match_limit", "10", "--ban_length", "3", "--rule_match_limit", "x+0=x=7",
+        ]);
+        let mut config = GenerationConfig::default();
+        apply_args(cli, &mut config);
+
+        let saturation = config.saturation();
+        assert_eq!(saturation.iter_limit, 2);
+        assert_eq!(saturation.node_limit, 50_000);
+        assert_eq!(saturation.time_limit, std::time::Duration::from_secs(30));
+        assert_eq!(saturation.scheduler, SaturationScheduler::Backoff {
+            match_limit: 10,
+            ban_length: 3,
+            rule_match_limits: vec![("x+0=x".to_string(), 7)],
+            rule_ban_lengths: vec![],
+        });
 
-    return cli_dtype;
+        /* the limits reach the Runner of equality saturation */
+        let mut ctx_gr = ContextGrammar::new("(* (sin x) (cos x))".to_string()).with_saturation(saturation);
+        ctx_gr.setup().unwrap();
+        assert!(matches!(ctx_gr.stop_reason, Some(StopReason::IterationLimit(2))));
+
+        let cli = Cli::parse_from(["egen", "--scheduler", "simple"]);
+        let mut config = GenerationConfig::default();
+        apply_args(cli, &mut config);
+        assert_eq!(config.saturation().scheduler, SaturationScheduler::Simple);
+        assert_eq!(GenerationConfig::default().saturation(), SaturationConfig::default());
+    }
 }
@@ -1,19 +1,392 @@
 use crate::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+/* default hyperparameters */
+/// default optimized extraction flag
+pub const OPTIMIZED: bool = false;
+/// default number of equivalent expressions
+pub const N_EQUIV_EXPRS: usize = 10;
+/// default initial token limit
+pub const TOKEN_LIMIT: u16 = 8;
+/// default max token limit
+pub const MAX_TOKEN_LIMIT: u16 = 12;
+/// default initial time limit in sec
+pub const TIME_LIMIT: u32 = 350;
+/// default token limit step of fixed escalation
+pub const TOKEN_STEP: u16 = 1;
+/// default time limit step of fixed escalation in sec
+pub const TIME_STEP: u32 = 300;
+/// default token limit factor of multiplicative escalation
+pub const TOKEN_FACTOR: f64 = 1.5;
+/// default time limit factor of multiplicative escalation
+pub const TIME_FACTOR: f64 = 2.0;
+/// default candidate pool factor of diverse selection
+pub const POOL_FACTOR: usize = 1;
+/// default penalty per level of AST depth of depth-penalized cost
+pub const DEPTH_PENALTY: f64 = 0.5;
+/// default oversampling factor of early termination
+pub const OVERSAMPLE: f64 = 1.0;
+/// default number of parallel jobs
+pub const JOBS: u16 = 1;
 
 /* global variables */
-/// optimized extraction flag
-pub static mut OPTIMIZED: bool = false;
-/// number of equivalent expressions
-pub static mut N_EQUIV_EXPRS: u8 = 10;
-/// token limit
-pub static mut TOKEN_LIMIT: u8 = 8;
-/// max token limit
-pub static mut MAX_TOKEN_LIMIT: u8 = 12;
-/// time limit in sec
-pub static mut TIME_LIMIT: u16 = 300;
-/// start time
-pub static mut START_TIME: Option<Instant> = None;
 /// log level for the entire environment
 pub static LOG_LEVEL: LogLevel = LogLevel::Info;
 /// suppress meaningless rewrite rules (e.g. * 1, pow 1, + 0)
 pub static SUPPRESS: bool = true;
+
+/// Generation Config Struct
+/// store hyperparameters and input/output settings of a generation run,
+/// loaded from a TOML/JSON config file and overridden by command line flags,
+/// missing fields take the default values
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GenerationConfig {
+    /// optimized extraction flag
+    pub optimized: bool,
+    /// extraction strategy
+    pub extraction: ExtractionKind,
+    /// random seed of the production order and sample extraction
+    pub seed: u64,
+    /// reproducible generation, default with the deterministic cargo feature
+    pub deterministic: bool,
+    /// cost function of top-k extraction
+    pub cost: CostKind,
+    /// selection of the returned equivalent expressions
+    pub selection: SelectionKind,
+    /// candidate pool size as a multiple of the number of equivalent expressions
+    pub pool_factor: usize,
+    /// stop extraction once enough distinct expressions are found
+    pub early_stop: bool,
+    /// oversampling factor of the number of expressions for early termination
+    pub oversample: f64,
+    /// number of equivalent expressions
+    pub n_equiv_exprs: usize,
+    /// initial token limit
+    pub init_token_limit: u16,
+    /// maximum token limit
+    pub max_token_limit: u16,
+    /// initial time limit in sec
+    pub init_time_limit: u32,
+    /// token/time limits escalation strategy
+    pub escalation: EscalationKind,
+    /// token limit step of fixed escalation
+    pub token_step: u16,
+    /// time limit step of fixed escalation in sec
+    pub time_step: u32,
+    /// token limit factor of multiplicative escalation
+    pub token_factor: f64,
+    /// time limit factor of multiplicative escalation
+    pub time_factor: f64,
+    /// total time budget of an expression (saturation and extraction rounds) in sec
+    pub time_budget: Option<u64>,
+    /// stop escalation when a round adds no new expression
+    pub stop_no_progress: bool,
+    /// skip expressions with fewer terms than the number of equivalent
+    /// expressions within the maximum token limit
+    pub skip_unreachable: bool,
+    /// raise the initial token limit to the smallest limit with enough terms
+    pub raise_token_limit: bool,
+    /// extract derivative-free expressions only (without `d` and `i`)
+    pub derivative_free: bool,
+    /// filters of the productions, sentential forms and expressions, `identity`,
+    /// `derivative_free`, `op:<op>`, `pattern:<pattern>` or `depth:<max depth>`
+    pub filters: Vec<String>,
+    /// dedupe equivalent expressions modulo variable renaming
+    pub alpha_rename: bool,
+    /// dedupe equivalent expressions modulo numeric literal spelling
+    pub normalize_constants: bool,
+    /// iteration limit of equality saturation
+    pub iter_limit: usize,
+    /// e-node limit of equality saturation
+    pub node_limit: usize,
+    /// time limit of equality saturation in sec
+    pub sat_time_limit: f64,
+    /// rewrite scheduler of equality saturation
+    pub scheduler: SchedulerKind,
+    /// initial match limit of every rule for backoff scheduler
+    pub match_limit: usize,
+    /// initial ban length of every rule for backoff scheduler
+    pub ban_length: usize,
+    /// per-rule initial match limits for backoff scheduler
+    pub rule_match_limits: BTreeMap<String, usize>,
+    /// per-rule initial ban lengths for backoff scheduler
+    pub rule_ban_lengths: BTreeMap<String, usize>,
+    /// input expression
+    pub input_expr: Option<String>,
+    /// input filepath
+    pub input_filepath: Option<String>,
+    /// output filepath
+    pub output_filepath: Option<String>,
+    /// number of parallel jobs
+    pub jobs: u16,
+    /// resume from the checkpoint manifest of the output file
+    pub resume: bool,
+    /// output file format
+    pub format: OutputFormat,
+    /// per-expression statistics filepath
+    pub stats_filepath: Option<String>,
+}
+
+impl Default for GenerationConfig {
+    fn default() -> Self {
+        let saturation = SaturationConfig::default();
+        let (match_limit, ban_length) = match saturation.scheduler {
+            SaturationScheduler::Backoff { match_limit, ban_length, .. } => { (match_limit, ban_length) },
+            SaturationScheduler::Simple => { (1_000, 5) },
+        };
+
+        return GenerationConfig {
+            optimized: OPTIMIZED,
+            extraction: ExtractionKind::Dfs,
+            seed: 0,
+            deterministic: cfg!(feature = "deterministic"),
+            cost: CostKind::Size,
+            selection: SelectionKind::All,
+            pool_factor: POOL_FACTOR,
+            early_stop: false,
+            oversample: OVERSAMPLE,
+            n_equiv_exprs: N_EQUIV_EXPRS,
+            init_token_limit: TOKEN_LIMIT,
+            max_token_limit: MAX_TOKEN_LIMIT,
+            init_time_limit: TIME_LIMIT,
+            escalation: EscalationKind::Fixed,
+            token_step: TOKEN_STEP,
+            time_step: TIME_STEP,
+            token_factor: TOKEN_FACTOR,
+            time_factor: TIME_FACTOR,
+            time_budget: None,
+            stop_no_progress: false,
+            skip_unreachable: false,
+            raise_token_limit: false,
+            derivative_free: false,
+            alpha_rename: false,
+            normalize_constants: false,
+            filters: if SUPPRESS { vec!["identity".to_string()] } else { vec![] },
+            iter_limit: saturation.iter_limit,
+            node_limit: saturation.node_limit,
+            sat_time_limit: saturation.time_limit.as_secs_f64(),
+            scheduler: SchedulerKind::Backoff,
+            match_limit,
+            ban_length,
+            rule_match_limits: Default::default(),
+            rule_ban_lengths: Default::default(),
+            input_expr: None,
+            input_filepath: None,
+            output_filepath: None,
+            jobs: JOBS,
+            resume: false,
+            format: OutputFormat::Text,
+            stats_filepath: None,
+        };
+    }
+}
+
+impl GenerationConfig {
+    /// ### constructor to load a config file,
+    /// ### the format is decided by the file extension (.toml or .json)
+    /// #### Argument
+    /// * `filepath` - config filepath
+    /// #### Return
+    /// * `Result` - config, or config error
+    pub fn from_file(filepath: &str) -> Result<Self, ConfigError> {
+        let content = match std::fs::read_to_string(filepath) {
+            Ok(content) => { content },
+            Err(e) => {
+                return Err(ConfigError::Io { filepath: filepath.to_string(), reason: e.to_string() });
+            },
+        };
+        let extension = Path::new(filepath).extension().and_then(|extension| extension.to_str());
+        let config = match extension {
+            Some("toml") => { toml::from_str::<GenerationConfig>(&content).map_err(|e| e.to_string()) },
+            Some("json") => { serde_json::from_str::<GenerationConfig>(&content).map_err(|e| e.to_string()) },
+            _ => { return Err(ConfigError::Format(filepath.to_string())); },
+        };
+
+        return config.map_err(|reason| ConfigError::Parse { filepath: filepath.to_string(), reason });
+    }
+
+    /// ### member function to check the hyperparameters
+    /// ### and the input/output settings
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `Result` - empty, or the first invalid setting
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |reason: String| { return Err(ConfigError::Invalid(reason)); };
+
+        if self.n_equiv_exprs == 0 {
+            return invalid("number of equivalent expressions needs to be > 0".to_string());
+        }
+        if self.pool_factor == 0 {
+            return invalid("candidate pool factor needs to be > 0".to_string());
+        }
+        if self.oversample.is_nan() || self.oversample < 1.0 || self.oversample.is_infinite() {
+            return invalid(format!("oversampling factor {} needs to be ≥ 1", self.oversample));
+        }
+        if self.init_token_limit == 0 {
+            return invalid("initial token limit needs to be > 0".to_string());
+        }
+        if self.max_token_limit < self.init_token_limit {
+            return invalid(format!("maximum token limit {} needs to be ≥ initial token limit {}",
+                                   self.max_token_limit, self.init_token_limit));
+        }
+        if self.init_time_limit == 0 {
+            return invalid("initial time limit needs to be > 0".to_string());
+        }
+        if self.token_step == 0 {
+            return invalid("token limit step needs to be > 0".to_string());
+        }
+        if self.token_factor.is_nan() || self.token_factor < 1.0 || self.time_factor.is_nan() || self.time_factor < 1.0 {
+            return invalid(format!("escalation factors {} and {} need to be ≥ 1", self.token_factor, self.time_factor));
+        }
+        if self.time_budget == Some(0) {
+            return invalid("time budget needs to be > 0".to_string());
+        }
+        if self.sat_time_limit.is_nan() || self.sat_time_limit <= 0.0 || self.sat_time_limit.is_infinite() {
+            return invalid(format!("saturation time limit {} needs to be > 0", self.sat_time_limit));
+        }
+        if self.jobs == 0 {
+            return invalid("number of jobs needs to be > 0".to_string());
+        }
+        for filter in &self.filters {
+            if let Err(reason) = parse_filter(filter) { return invalid(reason); }
+        }
+        match (&self.input_expr, &self.input_filepath, &self.output_filepath) {
+            (Some(_), None, None) => {},
+            (None, Some(_), Some(_)) => {},
+            (Some(_), _, _) => {
+                return invalid("input expression conflicts with input/output filepaths".to_string());
+            },
+            (None, _, _) => {
+                return invalid("expect an input expression, or both input and output filepaths".to_string());
+            },
+        }
+
+        return Ok(());
+    }
+
+    /// ### member function to build the escalation strategy
+    /// ### of token/time limits, the time budget is enforced by
+    /// ### budget() rather than the escalation
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `Box<dyn Escalation>` - escalation strategy
+    pub fn escalation(&self) -> Box<dyn Escalation> {
+        let mut escalation: Box<dyn Escalation> = match self.escalation {
+            EscalationKind::Fixed => { Box::new(FixedEscalation::new(self.token_step, self.time_step)) },
+            EscalationKind::Multiplicative => {
+                Box::new(MultiplicativeEscalation::new(self.token_factor, self.time_factor))
+            },
+        };
+        if self.stop_no_progress {
+            escalation = Box::new(StopOnNoProgress::new(escalation));
+        }
+
+        return escalation;
+    }
+
+    /// ### member function to build the filters of the productions,
+    /// ### sentential forms and expressions, invalid filters are
+    /// ### rejected by validate() already
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `Vec<Box<dyn ExprFilter>>` - filters
+    pub fn filters(&self) -> Vec<Box<dyn ExprFilter>> {
+        return self.filters.iter().filter_map(|filter| parse_filter(filter).ok()).collect();
+    }
+
+    /// ### member function to build the equivalences of the dedupe
+    /// ### of equivalent expressions
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `Canonicalization` - canonicalization
+    pub fn canonicalization(&self) -> Canonicalization {
+        return Canonicalization { alpha_rename: self.alpha_rename, normalize_constants: self.normalize_constants };
+    }
+
+    /// ### member function to build a new budget of 1 generation,
+    /// ### starting now with the total time budget
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `Budget` - budget of the generation
+    pub fn budget(&self) -> Budget {
+        return Budget::new(self.time_budget.map(Duration::from_secs));
+    }
+
+    /// ### member function to build the Runner limits and
+    /// ### rewrite scheduler of equality saturation
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `SaturationConfig` - saturation config
+    pub fn saturation(&self) -> SaturationConfig {
+        let scheduler = match self.scheduler {
+            SchedulerKind::Simple => { SaturationScheduler::Simple },
+            SchedulerKind::Backoff => {
+                SaturationScheduler::Backoff {
+                    match_limit: self.match_limit,
+                    ban_length: self.ban_length,
+                    rule_match_limits: self.rule_match_limits.clone().into_iter().collect(),
+                    rule_ban_lengths: self.rule_ban_lengths.clone().into_iter().collect(),
+                }
+            },
+        };
+
+        return SaturationConfig {
+            iter_limit: self.iter_limit,
+            node_limit: self.node_limit,
+            time_limit: Duration::from_secs_f64(self.sat_time_limit),
+            scheduler,
+        };
+    }
+
+    /// ### member function to convert the config to JSON,
+    /// ### the JSON can be loaded back as a config file
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `Value` - JSON object of the config
+    pub fn to_json(&self) -> serde_json::Value {
+        return serde_json::to_value(self).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_and_validate() {
+        let filepath = std::env::temp_dir().join(format!("egen_config_{}.toml", std::process::id()));
+        let filepath = filepath.to_str().unwrap();
+        std::fs::write(filepath, "n_equiv_exprs = 500\nmax_token_limit = 300\ninput_expr = \"(cos x)\"\n\
+                                  escalation = \"multiplicative\"\n[rule_match_limits]\n\"comm-add\" = 10\n").unwrap();
+
+        let config = GenerationConfig::from_file(filepath).unwrap();
+        assert_eq!(config.n_equiv_exprs, 500);
+        assert_eq!(config.max_token_limit, 300);
+        assert_eq!(config.init_token_limit, TOKEN_LIMIT);
+        assert_eq!(config.escalation, EscalationKind::Multiplicative);
+        assert_eq!(config.rule_match_limits.get("comm-add"), Some(&10));
+        assert!(config.validate().is_ok());
+
+        /* echoed JSON loads back into the same config */
+        let echoed: GenerationConfig = serde_json::from_value(config.to_json()).unwrap();
+        assert_eq!(echoed, config);
+
+        std::fs::write(filepath, "n_equiv_exprs = 5\nunknown = 1\n").unwrap();
+        assert!(matches!(GenerationConfig::from_file(filepath), Err(ConfigError::Parse { .. })));
+        let config = GenerationConfig { max_token_limit: 4, ..Default::default() };
+        assert!(matches!(config.validate(), Err(ConfigError::Invalid(_))));
+
+        std::fs::remove_file(filepath).unwrap();
+    }
+}
@@ -1,23 +1,87 @@
 use crate::*;
 use quanta::Instant;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
+
+/// Saturation Scheduler
+/// rewrite scheduler of equality saturation
+#[derive(Clone, Debug, PartialEq)]
+pub enum SaturationScheduler {
+    /// run every rewrite rule every iteration
+    Simple,
+    /// ban rewrite rules that exceed their match limits, with exponential backoff
+    Backoff {
+        /// initial match limit of every rule
+        match_limit: usize,
+        /// initial ban length (iterations) of every rule
+        ban_length: usize,
+        /// per-rule initial match limits (rule name, match limit)
+        rule_match_limits: Vec<(String, usize)>,
+        /// per-rule initial ban lengths (rule name, ban length)
+        rule_ban_lengths: Vec<(String, usize)>,
+    },
+}
+
+/// Saturation Config Struct
+/// store the Runner limits and the rewrite scheduler of equality saturation,
+/// defaults are the Runner defaults
+#[derive(Clone, Debug, PartialEq)]
+pub struct SaturationConfig {
+    /// iteration limit
+    pub iter_limit: usize,
+    /// e-node limit
+    pub node_limit: usize,
+    /// time limit
+    pub time_limit: Duration,
+    /// rewrite scheduler
+    pub scheduler: SaturationScheduler,
+}
+
+impl Default for SaturationConfig {
+    fn default() -> Self {
+        return SaturationConfig {
+            iter_limit: 30,
+            node_limit: 10_000,
+            time_limit: Duration::from_secs(5),
+            scheduler: SaturationScheduler::Backoff {
+                match_limit: 1_000,
+                ban_length: 5,
+                rule_match_limits: vec![],
+                rule_ban_lengths: vec![],
+            },
+        };
+    }
+}
 
 /// Context Grammar Struct
-/// store information about initial expression,
-/// egraph, root eclass(es), skip eclass(es),
-/// grammar, initial rewrite
+/// store information about initial expression(s),
+/// egraph, root eclass(es),
+/// grammar, initial rewrite(s) of every root eclass
 pub struct ContextGrammar {
-    /// initial expression to run with egraph
-    input_expr: String,
+    /// initial expression(s) to run with egraph, 1 root eclass per expression
+    input_exprs: Vec<String>,
+    /// Runner limits and rewrite scheduler of equality saturation
+    saturation: SaturationConfig,
+    /// random seed of the production order, 0 for the canonical order
+    seed: u64,
+    /// budget of the generation, saturation stops once it is exhausted
+    budget: Budget,
     /// egraph after running rewrite rules
     pub egraph: MathEGraph,
     /// root eclass(es) of MathEGraph
     pub root_eclasses: Vec<Id>,
-    /// eclass(es) to skip during extract
-    pub skip_eclasses: HashMap<String, f64>,
     /// grammar generated from e-graph
-    pub grammar: HashMap<String, Vec<String>>,
-    /// initial rw e.g. (* e0 e1)
-    pub init_exprs: Vec<String>,
+    pub grammar: Grammar,
+    /// initial rw e.g. (* e0 e1) of every root eclass, in insertion order
+    pub init_exprs: Vec<Vec<Math>>,
+    /// reason the equality saturation stopped
+    pub stop_reason: Option<StopReason>,
+    /// # of equality saturation iteration(s)
+    pub n_iterations: usize,
+    /// equality saturation time in sec
+    pub saturation_time: f64,
+    /// grammar creation time in sec
+    pub grammar_time: f64,
 }
 
 impl ContextGrammar {
@@ -28,82 +92,203 @@ impl ContextGrammar {
     /// * `None`
     pub fn new(input_expr: String) -> Self {
         ContextGrammar {
-            input_expr,
+            input_exprs: vec![input_expr],
+            saturation: Default::default(),
+            seed: 0,
+            budget: Default::default(),
             egraph: Default::default(),
             root_eclasses: vec![],
-            skip_eclasses: Default::default(),
             grammar: Default::default(),
             init_exprs: vec![],
+            stop_reason: None,
+            n_iterations: 0,
+            saturation_time: 0.0,
+            grammar_time: 0.0,
         }
     }
 
+    /// ### member function to add another expression into the same egraph,
+    /// ### the expressions are saturated together and every expression
+    /// ### gets its own root eclass and initial rewrites
+    /// #### Arguments
+    /// * `self`
+    /// * `input_expr` - initial expression for rewriting
+    /// #### Return
+    /// * `ContextGrammar`
+    pub fn with_expr(mut self, input_expr: String) -> Self {
+        self.input_exprs.push(input_expr);
+        return self;
+    }
+
+    /// ### member function to change the random seed of the production order,
+    /// ### default is 0 (canonical order)
+    /// #### Arguments
+    /// * `self`
+    /// * `seed` - random seed
+    /// #### Return
+    /// * `ContextGrammar`
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        return self;
+    }
+
+    /// ### member function to change the budget of the generation,
+    /// ### default is no time limit
+    /// #### Arguments
+    /// * `self`
+    /// * `budget` - budget of the generation
+    /// #### Return
+    /// * `ContextGrammar`
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = budget;
+        return self;
+    }
+
+    /// ### member function to set Runner limits and rewrite scheduler
+    /// ### of equality saturation
+    /// #### Arguments
+    /// * `self`
+    /// * `saturation` - saturation config
+    /// #### Return
+    /// * `ContextGrammar`
+    pub fn with_saturation(mut self, saturation: SaturationConfig) -> Self {
+        self.saturation = saturation;
+        return self;
+    }
+
+    /// ### member function to set the iteration limit of equality saturation
+    /// #### Arguments
+    /// * `self`
+    /// * `iter_limit` - iteration limit
+    /// #### Return
+    /// * `ContextGrammar`
+    pub fn with_iter_limit(mut self, iter_limit: usize) -> Self {
+        self.saturation.iter_limit = iter_limit;
+        return self;
+    }
+
+    /// ### member function to set the e-node limit of equality saturation
+    /// #### Arguments
+    /// * `self`
+    /// * `node_limit` - e-node limit
+    /// #### Return
+    /// * `ContextGrammar`
+    pub fn with_node_limit(mut self, node_limit: usize) -> Self {
+        self.saturation.node_limit = node_limit;
+        return self;
+    }
+
+    /// ### member function to set the time limit of equality saturation
+    /// #### Arguments
+    /// * `self`
+    /// * `time_limit` - time limit
+    /// #### Return
+    /// * `ContextGrammar`
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.saturation.time_limit = time_limit;
+        return self;
+    }
+
+    /// ### member function to set the rewrite scheduler of equality saturation
+    /// #### Arguments
+    /// * `self`
+    /// * `scheduler` - rewrite scheduler
+    /// #### Return
+    /// * `ContextGrammar`
+    pub fn with_scheduler(mut self, scheduler: SaturationScheduler) -> Self {
+        self.saturation.scheduler = scheduler;
+        return self;
+    }
+
     /// ### member function to set egraph and root_eclasses
+    /// ### a panic of the egraph analysis during equality saturation
+    /// ### is caught and returned as an error
     /// #### Argument
     /// * `self`
     /// #### Return
-    /// * `None`
-    pub fn setup(&mut self) {
-        /* parse initial expression and create initial e-graph */
+    /// * `Result` - empty, or parse/analysis error
+    pub fn setup(&mut self) -> Result<(), GenError> {
+        /* parse initial expression(s) and create initial e-graph */
         let start_time = Instant::now();
-        let recexpr = self.input_expr.parse().unwrap();
-        let runner = Runner::default().with_expr(&recexpr);
+        let mut recexprs: Vec<RecExpr<Math>> = vec![];
+        for input_expr in &self.input_exprs {
+            match input_expr.parse() {
+                Ok(recexpr) => { recexprs.push(recexpr); },
+                Err(e) => {
+                    return Err(GenError::Parse { expr: input_expr.clone(), reason: e.to_string() });
+                },
+            }
+        }
+        let time_limit = match self.budget.remaining() {
+            Some(remaining) => { self.saturation.time_limit.min(remaining) },
+            None => { self.saturation.time_limit },
+        };
+        let budget = self.budget.clone();
+        let runner = Runner::default()
+            .with_iter_limit(self.saturation.iter_limit)
+            .with_node_limit(self.saturation.node_limit)
+            .with_time_limit(time_limit)
+            .with_hook(move |_| {
+                if budget.is_cancelled() { return Err("cancelled".to_string()); }
+                return Ok(());
+            });
+        let runner = match &self.saturation.scheduler {
+            SaturationScheduler::Simple => { runner.with_scheduler(SimpleScheduler) },
+            SaturationScheduler::Backoff { match_limit, ban_length, rule_match_limits, rule_ban_lengths } => {
+                let mut scheduler = BackoffScheduler::default()
+                    .with_initial_match_limit(*match_limit)
+                    .with_ban_length(*ban_length);
+                for (rule, limit) in rule_match_limits {
+                    scheduler = scheduler.rule_match_limit(rule.as_str(), *limit);
+                }
+                for (rule, length) in rule_ban_lengths {
+                    scheduler = scheduler.rule_ban_length(rule.as_str(), *length);
+                }
+                runner.with_scheduler(scheduler)
+            },
+        };
+        let runner = recexprs.iter().fold(runner, |runner, recexpr| runner.with_expr(recexpr));
 
         /* equality saturation */
-        let runner = runner.run(&math_rule());
+        let runner = match panic::catch_unwind(AssertUnwindSafe(|| runner.run(&math_rule()))) {
+            Ok(runner) => { runner },
+            Err(payload) => { return Err(GenError::Analysis(panic_message(&payload))); },
+        };
         let end_time = Instant::now();
         let elapsed_time = end_time.duration_since(start_time).as_secs();
         log_info(&format!("E-graph saturation time: {}s\n", elapsed_time));
+        self.saturation_time = end_time.duration_since(start_time).as_secs_f64();
 
         self.egraph = runner.egraph;
         self.root_eclasses = runner.roots;
-        let eclasses = self.egraph.classes();
+        self.stop_reason = runner.stop_reason;
+        self.n_iterations = runner.iterations.len();
 
-        /* setup member variables skip_ecls and grammar */
+        /* setup member variable grammar, identity rewrites are pruned by IdentityFilter */
         let start_time = Instant::now();
-        for eclass in eclasses {
-            let mut rewrite_rules: Vec<String> = vec![];
-            let ecls: String = format!("{}{}", "e", eclass.id);
-            let enodes = &eclass.nodes;
-
-            if enodes.len() == 1 {
-                match enodes[0].to_string().parse::<f64>() {
-                    Ok(float64) => {
-                        if float64 == 1.0 || float64 == 0.0 {
-                            self.skip_eclasses.insert(ecls.clone(), float64);
-                        }
-                    },
-                    Err(_) => {},
-                }
-            }
-
-            for enode in enodes {
-                let mut rewrite = enode.to_string();
-                let children = enode.children();
-                for child in children {
-                    rewrite = format!("{} {}{}", rewrite, "e", child);
-                }
-                rewrite_rules.push(rewrite);
-            }
-            self.grammar.insert(ecls, rewrite_rules);
-        }
+        self.grammar = Grammar::from_egraph(&self.egraph).with_seed(self.seed);
         let end_time = Instant::now();
         let elapsed_time = end_time.duration_since(start_time).as_secs();
         log_info(&format!("Grammar  creation  time: {}s\n", elapsed_time));
+        self.grammar_time = end_time.duration_since(start_time).as_secs_f64();
 
-        /* setup the member variable init_rw */
+        /* setup the member variable init_rw, 1 entry per root eclass */
+        self.init_exprs = vec![];
         for rc in &self.root_eclasses {
-            let mut root_ecls = format!("{}{}", "e", rc);
-            if self.grammar.contains_key(&*root_ecls) {
-                self.init_exprs = self.grammar.get(&*root_ecls).unwrap().clone();
-            } else {
-                root_ecls = format!("{}{}", "e", self.egraph.find(*rc));
-                self.init_exprs = self.grammar.get(&*root_ecls).unwrap().clone();
-            }
+            let root_ecls = self.egraph.find(*rc);
+            self.init_exprs.push(self.grammar.productions(root_ecls).to_vec());
         }
 
-        return;
+        return Ok(());
     }
 
+    /// ### member function to get the saturation config
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `saturation` - Runner limits and rewrite scheduler
+    pub fn get_saturation(&self) -> &SaturationConfig { return &self.saturation; }
+
     /// ### member function to get an reference to egraph
     /// #### Argument
     /// * `self`
@@ -118,24 +303,69 @@ impl ContextGrammar {
     /// * `root_eclasses` - root eclass(es) from egraph
     pub fn get_root_eclasses(&self) -> &Vec<Id> { return &self.root_eclasses; }
 
-    /// ### member function to get skip eclasses
-    /// #### Argument
-    /// * `self`
-    /// #### Return
-    /// * `skip_eclasses` - skip eclass(es) from egraph
-    pub fn get_skip_eclasses(&self) -> &HashMap<String, f64> { return &self.skip_eclasses; }
-
     /// ### member function to get grammars
     /// #### Argument
     /// * `self`
     /// #### Return
     /// * `grammar` - grammars created from egraph
-    pub fn get_grammar(&self) -> &HashMap<String, Vec<String>> { return &self.grammar; }
+    pub fn get_grammar(&self) -> &Grammar { return &self.grammar; }
 
     /// ### member function to get the initial rewrite from self
     /// #### Argument
     /// * `self`
     /// #### Return
-    /// * `init_rw` - initial rewrite rule(s)
-    pub fn get_init_rw(&self) -> &Vec<String> { return &self.init_exprs; }
+    /// * `init_rw` - initial rewrite rule(s) of every root eclass
+    pub fn get_init_rw(&self) -> &Vec<Vec<Math>> { return &self.init_exprs; }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ### function to saturate an input expression with a saturation config
+    /// #### Arguments
+    /// * `input_expr` - input expression
+    /// * `saturation` - Runner limits and rewrite scheduler
+    /// #### Return
+    /// * `ContextGrammar` - context grammar after setup
+    fn saturate(input_expr: &str, saturation: SaturationConfig) -> ContextGrammar {
+        let mut ctx_gr = ContextGrammar::new(input_expr.to_string()).with_saturation(saturation);
+        ctx_gr.setup().unwrap();
+        return ctx_gr;
+    }
+
+    #[test]
+    fn saturation_defaults() {
+        let input_expr = "(* (sin x) (cos x))";
+        let ctx_gr = saturate(input_expr, SaturationConfig::default());
+        let runner: Runner<Math, math::ConstantFold> = Runner::default().with_expr(&input_expr.parse().unwrap()).run(&math_rule());
+
+        assert_eq!(format!("{:?}", ctx_gr.stop_reason), format!("{:?}", runner.stop_reason));
+        assert_eq!(ctx_gr.n_iterations, runner.iterations.len());
+        assert_eq!(ctx_gr.egraph.number_of_classes(), runner.egraph.number_of_classes());
+        assert_eq!(ctx_gr.egraph.total_number_of_nodes(), runner.egraph.total_number_of_nodes());
+    }
+
+    #[test]
+    fn saturation_limits() {
+        let ctx_gr = saturate("(sin x)", SaturationConfig { iter_limit: 2, ..Default::default() });
+        assert!(matches!(ctx_gr.stop_reason, Some(StopReason::IterationLimit(2))));
+        assert_eq!(ctx_gr.n_iterations, 2);
+
+        let ctx_gr = saturate("(* (sin x) (cos x))", SaturationConfig { node_limit: 100, ..Default::default() });
+        assert!(matches!(ctx_gr.stop_reason, Some(StopReason::NodeLimit(_))));
+
+        /* a tight backoff bans rules the simple scheduler keeps applying */
+        let n_enodes = |scheduler: SaturationScheduler| {
+            let saturation = SaturationConfig { iter_limit: 3, scheduler, ..Default::default() };
+            return saturate("(* (sin x) (cos x))", saturation).egraph.total_number_of_nodes();
+        };
+        let backoff = SaturationScheduler::Backoff {
+            match_limit: 1,
+            ban_length: 5,
+            rule_match_limits: vec![],
+            rule_ban_lengths: vec![],
+        };
+        assert!(n_enodes(backoff) < n_enodes(SaturationScheduler::Simple));
+    }
 }
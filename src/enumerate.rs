@@ -0,0 +1,212 @@
+use crate::*;
+use std::rc::Rc;
+
+/// Term Struct
+/// a term derived from the grammar, sharing its subterms
+/// with every other term built from them
+#[derive(Debug)]
+pub struct Term {
+    /// operator (or leaf) of the term
+    enode: Math,
+    /// subterms of the children
+    children: Vec<Rc<Term>>,
+}
+
+impl Term {
+    /// ### member function to append the tokens of the term
+    /// ### in prefix notation
+    /// #### Arguments
+    /// * `self`
+    /// * `tokens` - tokens to append to
+    /// #### Return
+    /// * `None`
+    pub fn append_tokens(&self, tokens: &mut Vec<GrammarToken>) {
+        tokens.push(GrammarToken::Terminal(self.enode.clone()));
+        for child in &self.children {
+            child.append_tokens(tokens);
+        }
+
+        return;
+    }
+
+    /// ### member function to get the tokens of the term in prefix notation
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `Vec<GrammarToken>` - tokens of the term
+    pub fn tokens(&self) -> Vec<GrammarToken> {
+        let mut tokens = vec![];
+        self.append_tokens(&mut tokens);
+
+        return tokens;
+    }
+}
+
+/// Term Enumerator Struct
+/// enumerate the terms of every eclass by # of tokens bottom-up,
+/// the terms of an eclass with n tokens are built once from the
+/// memoized terms of its child eclasses and shared by all parents
+pub struct TermEnumerator {
+    /// grammar with the kept productions only
+    grammar: Grammar,
+    /// minimum # of tokens of every eclass
+    min_lens: HashMap<Id, usize>,
+    /// terms of an eclass with n tokens
+    memo: HashMap<(Id, usize), Rc<Vec<Rc<Term>>>>,
+    /// budget of the extraction
+    budget: Budget,
+    /// whether the budget is exhausted
+    timed_out: bool,
+}
+
+impl TermEnumerator {
+    /// ### constructor
+    /// #### Arguments
+    /// * `grammar` - grammar from MathEGraph, pruned already (see Grammar::filtered)
+    /// #### Return
+    /// * `TermEnumerator`
+    pub fn new(grammar: &Grammar) -> Self {
+        let grammar = grammar.clone();
+        let min_lens = grammar.min_lens();
+
+        return TermEnumerator { grammar, min_lens, memo: Default::default(), budget: Default::default(), timed_out: false };
+    }
+
+    /// ### member function to stop the enumeration once the budget
+    /// ### is exhausted (out of time or cancelled),
+    /// ### terms enumerated afterwards are incomplete
+    /// #### Arguments
+    /// * `self`
+    /// * `budget` - budget of the extraction
+    /// #### Return
+    /// * `TermEnumerator`
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = budget;
+        return self;
+    }
+
+    /// ### member function to check if the budget is exhausted
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `bool` - whether the budget is exhausted or not
+    pub fn timed_out(&self) -> bool { return self.timed_out; }
+
+    /// ### member function to get the terms of an eclass
+    /// ### with exactly `n_tokens` tokens, memoized
+    /// #### Arguments
+    /// * `self`
+    /// * `eclass` - canonical eclass
+    /// * `n_tokens` - # of tokens
+    /// #### Return
+    /// * `Rc<Vec<Rc<Term>>>` - terms
+    pub fn terms(&mut self, eclass: Id, n_tokens: usize) -> Rc<Vec<Rc<Term>>> {
+        if let Some(terms) = self.memo.get(&(eclass, n_tokens)) { return terms.clone(); }
+
+        let mut terms = vec![];
+        if self.min_lens.get(&eclass).map_or(false, |min_len| *min_len <= n_tokens) {
+            let enodes = self.grammar.productions(eclass).to_vec();
+            for enode in &enodes {
+                terms.extend(self.terms_of(enode, n_tokens));
+            }
+        }
+        let terms = Rc::new(terms);
+        self.memo.insert((eclass, n_tokens), terms.clone());
+
+        return terms;
+    }
+
+    /// ### member function to get the terms rooted at an enode
+    /// ### with exactly `n_tokens` tokens, not memoized
+    /// #### Arguments
+    /// * `self`
+    /// * `enode` - production
+    /// * `n_tokens` - # of tokens
+    /// #### Return
+    /// * `Vec<Rc<Term>>` - terms
+    pub fn terms_of(&mut self, enode: &Math, n_tokens: usize) -> Vec<Rc<Term>> {
+        if n_tokens == 0 { return vec![]; }
+
+        return self
+            .children_terms(enode.children(), n_tokens - 1)
+            .into_iter()
+            .map(|children| Rc::new(Term { enode: enode.clone(), children }))
+            .collect();
+    }
+
+    /// ### private member function to get every combination of the terms
+    /// ### of the child eclass(es) with `n_tokens` tokens in total
+    /// #### Arguments
+    /// * `self`
+    /// * `children` - child eclass(es)
+    /// * `n_tokens` - # of tokens of all child eclass(es)
+    /// #### Return
+    /// * `Vec<Vec<Rc<Term>>>` - combinations of child terms
+    fn children_terms(&mut self, children: &[Id], n_tokens: usize) -> Vec<Vec<Rc<Term>>> {
+        let (child, rest) = match children.split_first() {
+            Some(split) => { split },
+            None => { return if n_tokens == 0 { vec![vec![]] } else { vec![] }; },
+        };
+        let rest_min_len: usize = match rest.iter().map(|child| self.min_lens.get(child)).sum::<Option<usize>>() {
+            Some(rest_min_len) => { rest_min_len },
+            None => { return vec![]; },
+        };
+
+        let mut combinations = vec![];
+        for child_len in 1..=n_tokens.saturating_sub(rest_min_len) {
+            if self.budget.is_exhausted() {
+                self.timed_out = true;
+                break;
+            }
+
+            let child_terms = self.terms(*child, child_len);
+            if child_terms.is_empty() { continue; }
+            let rest_terms = self.children_terms(rest, n_tokens - child_len);
+            for child_term in child_terms.iter() {
+                for rest_term in &rest_terms {
+                    let mut combination = Vec::with_capacity(children.len());
+                    combination.push(child_term.clone());
+                    combination.extend(rest_term.iter().cloned());
+                    combinations.push(combination);
+                }
+            }
+        }
+
+        return combinations;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigUint;
+
+    #[test]
+    fn enumerate_terms() {
+        let mut egraph = MathEGraph::default();
+        let x = egraph.add_expr(&"x".parse().unwrap());
+        let root = egraph.add_expr(&"(+ x (* x 1))".parse().unwrap());
+        let mul = egraph.add_expr(&"(* x 1)".parse().unwrap());
+        egraph.union(x, mul);
+        egraph.rebuild();
+        let root = egraph.find(root);
+        let grammar = Grammar::from_egraph(&egraph);
+
+        let term_counts = TermCounts::new(&grammar, 7);
+        let mut enumerator = TermEnumerator::new(&grammar);
+        for n_tokens in 1..=7 {
+            let terms = enumerator.terms(root, n_tokens);
+            assert_eq!(BigUint::from(terms.len()), term_counts.count(root, n_tokens));
+            let exprs: HashSet<String> = terms.iter().map(|term| tokens_to_string(&term.tokens())).collect();
+            assert_eq!(exprs.len(), terms.len());
+        }
+        assert!(enumerator.terms(root, 3).iter().any(|term| tokens_to_string(&term.tokens()) == "+ x x"));
+
+        /* the terms of an eclass are built once and shared by all parents */
+        let x = egraph.find(x);
+        assert!(Rc::ptr_eq(&enumerator.terms(x, 3), &enumerator.terms(x, 3)));
+        let leaf = enumerator.terms(x, 1)[0].clone();
+        let add = enumerator.terms(root, 3).iter().find(|term| tokens_to_string(&term.tokens()) == "+ x x").unwrap().clone();
+        assert!(add.children.iter().all(|child| Rc::ptr_eq(child, &leaf)));
+    }
+}
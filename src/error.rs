@@ -0,0 +1,71 @@
+use std::any::Any;
+use thiserror::Error;
+
+/// Generation Error
+/// reason a generation of equivalent expressions failed
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum GenError {
+    /// input expression is not a valid s-expression of Math
+    #[error("failed to parse input expression '{expr}': {reason}")]
+    Parse {
+        /// input expression
+        expr: String,
+        /// parser error message
+        reason: String,
+    },
+    /// egraph analysis failed during equality saturation
+    #[error("equality saturation failed: {0}")]
+    Analysis(String),
+    /// generation panicked after equality saturation
+    #[error("generation of input expression '{expr}' panicked: {reason}")]
+    Panic {
+        /// input expression
+        expr: String,
+        /// panic message
+        reason: String,
+    },
+}
+
+/// Config Error
+/// reason a generation config is rejected
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum ConfigError {
+    /// config file cannot be read
+    #[error("failed to read config file '{filepath}': {reason}")]
+    Io {
+        /// config filepath
+        filepath: String,
+        /// io error message
+        reason: String,
+    },
+    /// config file extension is neither .toml nor .json
+    #[error("unknown format of config file '{0}', expect .toml or .json")]
+    Format(String),
+    /// config file is not a valid config
+    #[error("failed to parse config file '{filepath}': {reason}")]
+    Parse {
+        /// config filepath
+        filepath: String,
+        /// parser error message
+        reason: String,
+    },
+    /// config has an invalid setting
+    #[error("invalid config: {0}")]
+    Invalid(String),
+}
+
+/// ### function to get the message of a caught panic
+/// #### Argument
+/// * `payload` - payload of the caught panic
+/// #### Return
+/// * `String` - panic message
+pub(crate) fn panic_message(payload: &Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        return message.to_string();
+    }
+    if let Some(message) = payload.downcast_ref::<String>() {
+        return message.clone();
+    }
+
+    return "unknown panic".to_string();
+}
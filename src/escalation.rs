@@ -0,0 +1,217 @@
+use crate::*;
+
+/// Limits Struct
+/// token limit and time limit of 1 extraction round
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Limits {
+    /// token limit
+    pub token_limit: u16,
+    /// time limit in sec
+    pub time_limit: u32,
+}
+
+/// Escalation Trait
+/// strategy to escalate the token/time limits between extraction rounds
+/// until the number of equivalent expressions is reached
+pub trait Escalation: Send {
+    /// ### member function called when a generation starts
+    /// ### default implementation does nothing
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `None`
+    fn reset(&mut self) {}
+
+    /// ### member function to decide the limits of the next extraction round
+    /// #### Arguments
+    /// * `self`
+    /// * `rounds` - statistics of the finished extraction round(s)
+    /// * `elapsed` - elapsed generation time in sec
+    /// #### Return
+    /// * `Result` - limits of the next round, or reason to stop the generation
+    fn next_limits(&mut self, rounds: &[RoundStats], elapsed: f64) -> Result<Limits, GenStopReason>;
+}
+
+/// Fixed Escalation Struct
+/// increase the token limit and the time limit by fixed steps,
+/// default +1 token, +300 sec
+#[derive(Clone, Debug)]
+pub struct FixedEscalation {
+    /// token limit step
+    token_step: u16,
+    /// time limit step in sec
+    time_step: u32,
+}
+
+impl FixedEscalation {
+    /// ### default constructor
+    /// #### Arguments
+    /// * `token_step` - token limit step
+    /// * `time_step` - time limit step in sec
+    /// #### Return
+    /// * `FixedEscalation`
+    pub fn new(token_step: u16, time_step: u32) -> Self {
+        return FixedEscalation { token_step, time_step };
+    }
+}
+
+impl Default for FixedEscalation {
+    fn default() -> Self {
+        return FixedEscalation::new(TOKEN_STEP, TIME_STEP);
+    }
+}
+
+impl Escalation for FixedEscalation {
+    fn next_limits(&mut self, rounds: &[RoundStats], _elapsed: f64) -> Result<Limits, GenStopReason> {
+        let round = rounds.last().unwrap();
+
+        return Ok(Limits {
+            token_limit: round.token_limit.saturating_add(self.token_step),
+            time_limit: round.time_limit.saturating_add(self.time_step),
+        });
+    }
+}
+
+/// Multiplicative Escalation Struct
+/// multiply the token limit and the time limit by fixed factors,
+/// the token limit grows by at least 1 token
+#[derive(Clone, Debug)]
+pub struct MultiplicativeEscalation {
+    /// token limit factor
+    token_factor: f64,
+    /// time limit factor
+    time_factor: f64,
+}
+
+impl MultiplicativeEscalation {
+    /// ### default constructor
+    /// #### Arguments
+    /// * `token_factor` - token limit factor
+    /// * `time_factor` - time limit factor
+    /// #### Return
+    /// * `MultiplicativeEscalation`
+    pub fn new(token_factor: f64, time_factor: f64) -> Self {
+        return MultiplicativeEscalation { token_factor, time_factor };
+    }
+}
+
+impl Escalation for MultiplicativeEscalation {
+    fn next_limits(&mut self, rounds: &[RoundStats], _elapsed: f64) -> Result<Limits, GenStopReason> {
+        let round = rounds.last().unwrap();
+        let token_limit = (round.token_limit as f64 * self.token_factor).ceil().min(u16::MAX as f64) as u16;
+        let time_limit = (round.time_limit as f64 * self.time_factor).ceil().min(u32::MAX as f64) as u32;
+
+        return Ok(Limits {
+            token_limit: token_limit.max(round.token_limit.saturating_add(1)),
+            time_limit,
+        });
+    }
+}
+
+/// Time Budget Struct
+/// stop the generation once the total time budget across rounds is spent,
+/// the time limit of the next round is capped by the remaining budget
+pub struct TimeBudget {
+    /// escalation to decide the limits within the budget
+    escalation: Box<dyn Escalation>,
+    /// total time budget in sec
+    budget: u64,
+}
+
+impl TimeBudget {
+    /// ### default constructor
+    /// #### Arguments
+    /// * `escalation` - escalation to decide the limits within the budget
+    /// * `budget` - total time budget in sec
+    /// #### Return
+    /// * `TimeBudget`
+    pub fn new(escalation: impl Escalation + 'static, budget: u64) -> Self {
+        return TimeBudget { escalation: Box::new(escalation), budget };
+    }
+}
+
+impl Escalation for TimeBudget {
+    fn reset(&mut self) { self.escalation.reset(); }
+
+    fn next_limits(&mut self, rounds: &[RoundStats], elapsed: f64) -> Result<Limits, GenStopReason> {
+        let remaining = self.budget as f64 - elapsed;
+        if remaining < 1.0 {
+            log_info(&format!("Time budget {}s is spent.\n", self.budget));
+            return Err(GenStopReason::TimeBudget);
+        }
+        let mut limits = self.escalation.next_limits(rounds, elapsed)?;
+        limits.time_limit = limits.time_limit.min(remaining.floor().min(u32::MAX as f64) as u32);
+
+        return Ok(limits);
+    }
+}
+
+/// Stop On No Progress Struct
+/// stop the generation when a round adds no new equivalent expression
+/// compared with the previous round
+pub struct StopOnNoProgress {
+    /// escalation to decide the limits while making progress
+    escalation: Box<dyn Escalation>,
+}
+
+impl StopOnNoProgress {
+    /// ### default constructor
+    /// #### Argument
+    /// * `escalation` - escalation to decide the limits while making progress
+    /// #### Return
+    /// * `StopOnNoProgress`
+    pub fn new(escalation: impl Escalation + 'static) -> Self {
+        return StopOnNoProgress { escalation: Box::new(escalation) };
+    }
+}
+
+impl Escalation for StopOnNoProgress {
+    fn reset(&mut self) { self.escalation.reset(); }
+
+    fn next_limits(&mut self, rounds: &[RoundStats], elapsed: f64) -> Result<Limits, GenStopReason> {
+        if let [.., prev_round, round] = rounds {
+            if round.n_exprs_after <= prev_round.n_exprs_after {
+                log_info("No new expression in the last round.\n");
+                return Err(GenStopReason::NoProgress);
+            }
+        }
+
+        return self.escalation.next_limits(rounds, elapsed);
+    }
+}
+
+impl Escalation for Box<dyn Escalation> {
+    fn reset(&mut self) { (**self).reset(); }
+
+    fn next_limits(&mut self, rounds: &[RoundStats], elapsed: f64) -> Result<Limits, GenStopReason> {
+        return (**self).next_limits(rounds, elapsed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round(token_limit: u16, time_limit: u32, n_exprs_after: usize) -> RoundStats {
+        return RoundStats { token_limit, time_limit, n_exprs_after, ..Default::default() };
+    }
+
+    #[test]
+    fn escalation_strategies() {
+        let rounds = vec![round(8, 300, 3)];
+        let limits = FixedEscalation::default().next_limits(&rounds, 0.0).unwrap();
+        assert_eq!(limits, Limits { token_limit: 9, time_limit: 600 });
+
+        let limits = MultiplicativeEscalation::new(1.1, 2.0).next_limits(&rounds, 0.0).unwrap();
+        assert_eq!(limits, Limits { token_limit: 9, time_limit: 600 });
+
+        let mut budget = TimeBudget::new(FixedEscalation::default(), 500);
+        assert_eq!(budget.next_limits(&rounds, 350.0).unwrap().time_limit, 150);
+        assert_eq!(budget.next_limits(&rounds, 500.0), Err(GenStopReason::TimeBudget));
+
+        let mut no_progress = StopOnNoProgress::new(FixedEscalation::default());
+        assert!(no_progress.next_limits(&rounds, 0.0).is_ok());
+        let rounds = vec![round(8, 300, 3), round(9, 600, 3)];
+        assert_eq!(no_progress.next_limits(&rounds, 0.0), Err(GenStopReason::NoProgress));
+    }
+}
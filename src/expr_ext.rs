@@ -1,41 +1,10 @@
 use crate::*;
-use quanta::Instant;
-
-/// private global variable to store eclass(es) to skip during extraction
-static mut SKIP_ECLS: Option<HashMap<String, f64>> = None;
-/// private global variable to store grammar from MathEGraph
-static mut GRAMMAR: Option<HashMap<String, Vec<String>>> = None;
-/// private global variable to store intermediate extraction states
-static mut STATE: Option<HashSet<String>> = None;
-/// private global variable to store equivalent expression results
-static mut EQUIV_EXPRS: Option<HashSet<String>> = None;
-
-/// ### public function to get private global variable SKIP_ECLS
-/// #### Argument
-/// * `None`
-/// #### Return
-/// * `SKIP_ECLS` - immutable reference of global variable SKIP_ECLS
-pub unsafe fn get_global_skip_ecls() -> &'static HashMap<String, f64> {
-    return SKIP_ECLS.as_ref().unwrap();
-}
+use num_bigint::BigUint;
+use std::time::Duration;
 
-/// ### public function to get private global variable GRAMMAR
-/// #### Argument
-/// * `None`
-/// #### Return
-/// * `GRAMMAR` - immutable reference of global variable GRAMMAR
-pub unsafe fn get_global_grammar() -> &'static HashMap<String, Vec<String>> {
-    return GRAMMAR.as_ref().unwrap();
-}
-
-/// ### public function to get private global variable EQUIV_EXPRS
-/// #### Argument
-/// * `None`
-/// #### Return
-/// * `EQUIV_EXPRS` - immutable reference of global variable EQUIV_EXPRS
-pub unsafe fn get_global_equiv_exprs() -> &'static HashSet<String> {
-    return EQUIV_EXPRS.as_ref().unwrap();
-}
+/// maximum # of draws of sample extraction per target expression,
+/// filtered and duplicate samples included
+pub const MAX_DRAWS_PER_TARGET: usize = 16;
 
 /// ### private member function to check if an eclass appears in str
 /// #### Arguments
@@ -56,328 +25,468 @@ pub unsafe fn get_global_equiv_exprs() -> &'static HashSet<String> {
 //     return false;
 // }
 
-/// ### private member function to skip meaningless rewrite rule(s)
-/// #### Argument
-/// * `rw` - rewrite rule
-/// #### Return
-/// * `bool` - whether skip the current rewrite or not
-unsafe fn skip_rw(rw: &Vec<String>) -> bool {
-    for (eclass, constant) in SKIP_ECLS.as_ref().unwrap() {
-        if constant == &1.0f64 {
-            if (rw[0] == "*" || rw[0] == "pow") && rw.contains(eclass) { return true; }
-            else if rw[0] == "/" && rw[rw.len()-1] == *eclass { return true; }
-        } else if constant == &0.0f64 {
-            if rw[0] == "+" && rw.contains(eclass) { return true; }
-            else if rw[0] == "-" && rw[rw.len()-1] == *eclass { return true; }
-        } else {
-            log_fatal("Invalid Pattern in fn skip_rw !\n");
-        }
-    }
-    return false;
-}
-
 /// ### private function to check whether tokens contain eclass
 /// #### Arguments
 /// * `tokens` - tokens (expression)
 /// #### Return
 /// * `bool` - whether eclass exists in tokens or not
-fn contain_ecls(tokens: &Vec<String>) -> bool {
-    for token in tokens {
-        if token.len() >= 2 && token.starts_with("e") && token.chars().nth(1).unwrap().is_ascii_digit() {
-            return true;
-        }
-    }
-    return false;
+fn contain_ecls(tokens: &[GrammarToken]) -> bool {
+    return tokens.iter().any(|token| token.is_nonterminal());
 }
 
-/// ### private function to extract all equivalent mathematical expressions
-/// ### Context-Free Grammar
-/// #### Arguments
-/// * `tokens` - tokenized expression
-/// * `idx` - fn call idx for debugging purpose
-/// #### Return
-/// * `None`
-unsafe fn optimized_extract(mut tokens: Vec<String>, idx: u8) {
-    let start_time = get_start_time();
-    let end_time = Instant::now();
-    let elapsed_time = end_time.duration_since(start_time).as_secs();
-    if elapsed_time >= TIME_LIMIT as u64 {
+impl Generator {
+    /// ### private member function to check if a sentential form
+    /// ### is kept by every filter
+    /// #### Arguments
+    /// * `self`
+    /// * `tokens` - tokens of the sentential form
+    /// #### Return
+    /// * `bool` - whether the sentential form is kept or not
+    fn keep_partial(&self, tokens: &[GrammarToken]) -> bool {
+        return self.filters.iter().all(|filter| filter.keep_partial(tokens));
+    }
+
+    /// ### private member function to check if an extracted expression
+    /// ### is kept by every filter
+    /// #### Arguments
+    /// * `self`
+    /// * `tokens` - tokens of the extracted expression
+    /// #### Return
+    /// * `bool` - whether the expression is kept or not
+    fn keep_expr(&self, tokens: &[GrammarToken]) -> bool {
+        return self.filters.iter().all(|filter| filter.keep_expr(tokens));
+    }
+
+    /// ### private member function to record an extracted expression
+    /// ### if it is kept by every filter
+    /// #### Arguments
+    /// * `self`
+    /// * `tokens` - tokens of the extracted expression
+    /// #### Return
+    /// * `None`
+    fn add_equiv_expr(&mut self, tokens: &[GrammarToken]) {
+        if !self.keep_expr(tokens) { return; }
+
+        let final_expr = tokens_to_string(tokens);
+        log_trace_raw(&format!("[FINAL]: {}\n", final_expr));
+        self.insert_equiv_expr(final_expr);
+
         return;
     }
 
-    log_trace("-----------------------------------\n");
-    log_trace(&format!("Function Call {}\n", idx));
-    let global_state = STATE.as_mut().unwrap();
-    if global_state.contains(&tokens.join(" ")) {
+    /// ### private member function to record an extracted expression,
+    /// ### the extraction target is reached once there are enough
+    /// ### expressions distinct after post-processing (see Canonicalization)
+    /// #### Arguments
+    /// * `self`
+    /// * `final_expr` - extracted expression
+    /// #### Return
+    /// * `None`
+    fn insert_equiv_expr(&mut self, final_expr: String) {
+        if !self.equiv_exprs.insert(final_expr.clone()) { return; }
+        if let Some(n_target) = self.n_target() {
+            self.canonical_keys.insert(self.get_canonicalization().key(&final_expr));
+            self.target_reached = self.canonical_keys.len() >= n_target;
+        }
+
         return;
     }
-    global_state.insert(tokens.join(" "));
-    let prev_tokens = tokens.clone();
 
-    let mut term: bool = false;
+    /// ### private member function to extract all equivalent mathematical expressions
+    /// ### Context-Free Grammar
+    /// #### Arguments
+    /// * `self`
+    /// * `grammar` - grammar from MathEGraph
+    /// * `tokens` - tokenized expression
+    /// * `idx` - fn call idx for debugging purpose
+    /// #### Return
+    /// * `None`
+    fn optimized_extract(&mut self, grammar: &Grammar, mut tokens: Vec<GrammarToken>, idx: u32) {
+        if self.round_budget.is_exhausted() {
+            self.timed_out = true;
+            return;
+        }
+        if self.target_reached { return; }
 
-    for i in 0..tokens.len() {
-        if tokens.len() == 1 {
-            let global_equiv_exprs = EQUIV_EXPRS.as_mut().unwrap();
-            let final_expr = tokens.join(" ");
-            global_equiv_exprs.insert(final_expr.clone());
-            log_trace_raw(&format!("[FINAL]: {}\n", final_expr));
+        log_trace("-----------------------------------\n");
+        log_trace(&format!("Function Call {}\n", idx));
+        let state = &mut self.state;
+        if state.contains(&tokens) {
             return;
         }
+        state.insert(tokens.clone());
+        let prev_tokens = tokens.clone();
 
-        let op = &tokens[i];
-        let grammar = GRAMMAR.as_ref().unwrap();
+        let mut term: bool = false;
 
-        if op.len() == 1 || !op.starts_with('e') || op.starts_with("exp") ||
-            !grammar.contains_key(op) { continue; }
-        log_trace_raw(&format!("[ OP ]:  {}\n", op));
-        let rw_list = grammar.get(op).unwrap();
+        for i in 0..tokens.len() {
+            if tokens.len() == 1 {
+                self.add_equiv_expr(&tokens);
+                return;
+            }
 
-        for k in 0..rw_list.len() {
-            let rw = &rw_list[k];
-            log_trace_raw(&format!("[INIT]:  {:?}\n", tokens));
-            log_trace_raw(&format!("[ RW ]:  {:?}\n", rw));
+            let op = match tokens[i] {
+                GrammarToken::NonTerminal(eclass) if grammar.contains(eclass) => { eclass },
+                _ => { continue; },
+            };
+            log_trace_raw(&format!("[ OP ]:  e{}\n", op));
+            let rw_list = grammar.productions(op);
+            /* every production of the eclass is pruned */
+            if rw_list.is_empty() { break; }
+
+            for k in 0..rw_list.len() {
+                if self.target_reached { return; }
+                let rw = &rw_list[k];
+                /* token strings are only built when tracing */
+                if LOG_LEVEL >= LogLevel::Trace {
+                    log_trace_raw(&format!("[INIT]:  {}\n", tokens_to_string(&tokens)));
+                    log_trace_raw(&format!("[ RW ]:  {}\n", tokens_to_string(&production_tokens(rw))));
+                }
 
-            let rw_tokens: Vec<String> = rw.split_whitespace().map(|s| s.to_owned()).collect();
+                let rw_tokens = production_tokens(rw);
+
+                #[allow(unused_doc_comments)]
+                /// ```rust
+                /// /* Regex will solve indistinct eclass match in str.replacen() */
+                /// /* Original Code */
+                /// str = str.replacen(op, &*rw, 1);
+                /// /* Using Regex (has performance issue since it's slow) */
+                /// use regex::Regex;
+                /// let mat = Regex::new(&format!(r"\b{}\b", op)).unwrap().find(&str).unwrap();
+                /// str.replace_range(mat.start()..mat.end(), &rw);
+                /// ```
+                // replace_distinct_ecls(op, rw, &mut str);
+                tokens.splice(i..i+1, rw_tokens);
+                if LOG_LEVEL >= LogLevel::Trace {
+                    log_trace_raw(&format!("[AFTER]: {}\n", tokens_to_string(&tokens)));
+                }
 
-            if SUPPRESS {
-                if rw_tokens.len() == 3 && skip_rw(&rw_tokens) {
+                if tokens.len() > self.token_limit as usize {
+                    log_trace("STR exceeds length limit, Try another RW...\n");
                     if k == rw_list.len()-1 {
                         term = true;
                         break;
                     }
+                    tokens = prev_tokens.clone();
                     continue;
                 }
-            }
-
-            #[allow(unused_doc_comments)]
-            /// ```rust
-            /// /* Regex will solve indistinct eclass match in str.replacen() */
-            /// /* Original Code */
-            /// str = str.replacen(op, &*rw, 1);
-            /// /* Using Regex (has performance issue since it's slow) */
-            /// use regex::Regex;
-            /// let mat = Regex::new(&format!(r"\b{}\b", op)).unwrap().find(&str).unwrap();
-            /// str.replace_range(mat.start()..mat.end(), &rw);
-            /// ```
-            // replace_distinct_ecls(op, rw, &mut str);
-            tokens.splice(i..i+1, rw_tokens);
-            log_trace_raw(&format!("[AFTER]: {:?}\n", tokens));
-
-            if tokens.len() > TOKEN_LIMIT as usize {
-                log_trace("STR exceeds length limit, Try another RW...\n");
-                if k == rw_list.len()-1 {
-                    term = true;
-                    break;
-                }
-                tokens = prev_tokens.clone();
-                continue;
-            }
-            if !contain_ecls(&tokens) && k == rw_list.len()-1 {
-                let global_equiv_exprs = EQUIV_EXPRS.as_mut().unwrap();
-                let final_expr = tokens.join(" ");
-                global_equiv_exprs.insert(final_expr.clone());
-                log_trace_raw(&format!("[FINAL]: {}\n", final_expr));
-                term = true;
-                break;
-            } else if !contain_ecls(&tokens) {
-                let global_equiv_exprs = EQUIV_EXPRS.as_mut().unwrap();
-                let final_expr = tokens.join(" ");
-                global_equiv_exprs.insert(final_expr.clone());
-                tokens = prev_tokens.clone();
-                log_trace_raw(&format!("[FINAL]: {}\n", final_expr));
-            } else {
-                optimized_extract(tokens.clone(), idx+1);
-                let start_time = get_start_time();
-                let end_time = Instant::now();
-                let elapsed_time = end_time.duration_since(start_time).as_secs();
-                if elapsed_time >= TIME_LIMIT as u64 {
-                    return;
+                if !self.keep_partial(&tokens) {
+                    log_trace("STR is pruned by a filter, Try another RW...\n");
+                    if k == rw_list.len()-1 {
+                        term = true;
+                        break;
+                    }
+                    tokens = prev_tokens.clone();
+                    continue;
                 }
-
-                log_trace(&format!("Back to Function Call {}\n", idx));
-                tokens = prev_tokens.clone();
-                if k == rw_list.len()-1 {
+                if !contain_ecls(&tokens) && k == rw_list.len()-1 {
+                    self.add_equiv_expr(&tokens);
                     term = true;
                     break;
+                } else if !contain_ecls(&tokens) {
+                    self.add_equiv_expr(&tokens);
+                    tokens = prev_tokens.clone();
+                } else {
+                    self.optimized_extract(grammar, tokens.clone(), idx+1);
+                    if self.round_budget.is_exhausted() {
+                        self.timed_out = true;
+                        return;
+                    }
+                    if self.target_reached { return; }
+
+                    log_trace(&format!("Back to Function Call {}\n", idx));
+                    tokens = prev_tokens.clone();
+                    if k == rw_list.len()-1 {
+                        term = true;
+                        break;
+                    }
                 }
             }
+            if term { break; }
         }
-        if term { break; }
-    }
-    log_trace(&format!("Finish Function Call {}\n", idx));
-    log_trace("-----------------------------------\n");
+        log_trace(&format!("Finish Function Call {}\n", idx));
+        log_trace("-----------------------------------\n");
 
-    return;
-}
-
-/// ### private function to extract all equivalent mathematical expressions
-/// ### Context-Sensitive Grammar
-/// #### Arguments
-/// * `tokens` - tokenized expression
-/// * `idx` - fn call idx for debugging purpose
-/// #### Return
-/// * `None`
-unsafe fn exhaustive_extract(mut tokens: Vec<String>, idx: u8) {
-    let start_time = get_start_time();
-    let end_time = Instant::now();
-    let elapsed_time = end_time.duration_since(start_time).as_secs();
-    if elapsed_time >= TIME_LIMIT as u64 {
         return;
     }
 
-    log_trace("-----------------------------------\n");
-    log_trace(&format!("Function Call {}\n", idx));
-    let prev_tokens = tokens.clone();
-    // let expr: Vec<&str> = prev_expr.split_whitespace().collect();
-
-    let mut term: bool = false;
-
-    for i in 0..tokens.len() {
-        if tokens.len() == 1 {
-            let global_equiv_exprs = EQUIV_EXPRS.as_mut().unwrap();
-            let final_expr = tokens.join(" ");
-            global_equiv_exprs.insert(final_expr.clone());
-            log_trace_raw(&format!("[FINAL]: {}\n", final_expr));
+    /// ### private member function to extract all equivalent mathematical expressions
+    /// ### Context-Sensitive Grammar
+    /// #### Arguments
+    /// * `self`
+    /// * `grammar` - grammar from MathEGraph
+    /// * `tokens` - tokenized expression
+    /// * `idx` - fn call idx for debugging purpose
+    /// #### Return
+    /// * `None`
+    fn exhaustive_extract(&mut self, grammar: &Grammar, mut tokens: Vec<GrammarToken>, idx: u32) {
+        if self.round_budget.is_exhausted() {
+            self.timed_out = true;
             return;
         }
+        if self.target_reached { return; }
 
-        let op = &tokens[i];
-        let grammar = GRAMMAR.as_ref().unwrap();
+        log_trace("-----------------------------------\n");
+        log_trace(&format!("Function Call {}\n", idx));
+        let prev_tokens = tokens.clone();
+        // let expr: Vec<&str> = prev_expr.split_whitespace().collect();
 
-        if op.len() == 1 || !op.starts_with('e') || op.starts_with("exp") ||
-            !grammar.contains_key(op) { continue; }
-        log_trace_raw(&format!("[ OP ]:  {}\n", op));
-        let rw_list = grammar.get(op).unwrap();
+        let mut term: bool = false;
 
-        for k in 0..rw_list.len() {
-            let rw = &rw_list[k];
-            log_trace_raw(&format!("[INIT]:  {:?}\n", tokens));
-            log_trace_raw(&format!("[ RW ]:  {:?}\n", rw));
+        for i in 0..tokens.len() {
+            if tokens.len() == 1 {
+                self.add_equiv_expr(&tokens);
+                return;
+            }
 
-            let rw_tokens: Vec<String> = rw.split_whitespace().map(|s| s.to_owned()).collect();
+            let op = match tokens[i] {
+                GrammarToken::NonTerminal(eclass) if grammar.contains(eclass) => { eclass },
+                _ => { continue; },
+            };
+            log_trace_raw(&format!("[ OP ]:  e{}\n", op));
+            let rw_list = grammar.productions(op);
+            /* every production of the eclass is pruned */
+            if rw_list.is_empty() { break; }
+
+            for k in 0..rw_list.len() {
+                if self.target_reached { return; }
+                let rw = &rw_list[k];
+                /* token strings are only built when tracing */
+                if LOG_LEVEL >= LogLevel::Trace {
+                    log_trace_raw(&format!("[INIT]:  {}\n", tokens_to_string(&tokens)));
+                    log_trace_raw(&format!("[ RW ]:  {}\n", tokens_to_string(&production_tokens(rw))));
+                }
 
-            if SUPPRESS {
-                if rw_tokens.len() == 3 && skip_rw(&rw_tokens) {
+                let rw_tokens = production_tokens(rw);
+
+                #[allow(unused_doc_comments)]
+                /// ```rust
+                /// /* Regex will solve indistinct eclass match in str.replacen() */
+                /// /* Original Code */
+                /// str = str.replacen(op, &*rw, 1);
+                /// /* Using Regex (has performance issue since it's slow) */
+                /// use regex::Regex;
+                /// let mat = Regex::new(&format!(r"\b{}\b", op)).unwrap().find(&str).unwrap();
+                /// str.replace_range(mat.start()..mat.end(), &rw);
+                /// ```
+                // replace_distinct_ecls(op, rw, &mut str);
+                tokens.splice(i..i+1, rw_tokens);
+                if LOG_LEVEL >= LogLevel::Trace {
+                    log_trace_raw(&format!("[AFTER]: {}\n", tokens_to_string(&tokens)));
+                }
+
+                if tokens.len() > self.token_limit as usize {
+                    log_trace("STR exceeds length limit, Try another RW...\n");
                     if k == rw_list.len()-1 {
                         term = true;
                         break;
                     }
+                    tokens = prev_tokens.clone();
                     continue;
                 }
-            }
-
-            #[allow(unused_doc_comments)]
-            /// ```rust
-            /// /* Regex will solve indistinct eclass match in str.replacen() */
-            /// /* Original Code */
-            /// str = str.replacen(op, &*rw, 1);
-            /// /* Using Regex (has performance issue since it's slow) */
-            /// use regex::Regex;
-            /// let mat = Regex::new(&format!(r"\b{}\b", op)).unwrap().find(&str).unwrap();
-            /// str.replace_range(mat.start()..mat.end(), &rw);
-            /// ```
-            // replace_distinct_ecls(op, rw, &mut str);
-            tokens.splice(i..i+1, rw_tokens);
-            log_trace_raw(&format!("[AFTER]: {:?}\n", tokens));
-
-            if tokens.len() > TOKEN_LIMIT as usize {
-                log_trace("STR exceeds length limit, Try another RW...\n");
-                if k == rw_list.len()-1 {
+                if !self.keep_partial(&tokens) {
+                    log_trace("STR is pruned by a filter, Try another RW...\n");
+                    if k == rw_list.len()-1 {
+                        term = true;
+                        break;
+                    }
+                    tokens = prev_tokens.clone();
+                    continue;
+                }
+                if !contain_ecls(&tokens) && k == rw_list.len()-1 {
+                    self.add_equiv_expr(&tokens);
                     term = true;
                     break;
+                } else if !contain_ecls(&tokens) {
+                    self.add_equiv_expr(&tokens);
+                    tokens = prev_tokens.clone();
+                } else {
+                    self.exhaustive_extract(grammar, tokens.clone(), idx+1);
+                    if self.round_budget.is_exhausted() {
+                        self.timed_out = true;
+                        return;
+                    }
+                    if self.target_reached { return; }
+
+                    log_trace(&format!("Back to Function Call {}\n", idx));
+                    tokens = prev_tokens.clone();
                 }
-                tokens = prev_tokens.clone();
-                continue;
             }
-            if !contain_ecls(&tokens) && k == rw_list.len()-1 {
-                let global_equiv_exprs = EQUIV_EXPRS.as_mut().unwrap();
-                let final_expr = tokens.join(" ");
-                global_equiv_exprs.insert(final_expr.clone());
-                log_trace_raw(&format!("[FINAL]: {}\n", final_expr));
-                term = true;
-                break;
-            } else if !contain_ecls(&tokens) {
-                let global_equiv_exprs = EQUIV_EXPRS.as_mut().unwrap();
-                let final_expr = tokens.join(" ");
-                global_equiv_exprs.insert(final_expr.clone());
-                tokens = prev_tokens.clone();
-                log_trace_raw(&format!("[FINAL]: {}\n", final_expr));
-            } else {
-                exhaustive_extract(tokens.clone(), idx+1);
-                let start_time = get_start_time();
-                let end_time = Instant::now();
-                let elapsed_time = end_time.duration_since(start_time).as_secs();
-                if elapsed_time >= TIME_LIMIT as u64 {
+            if term { break; }
+        }
+        log_trace(&format!("Finish Function Call {}\n", idx));
+        log_trace("-----------------------------------\n");
+
+        return;
+    }
+
+    /// ### private member function to extract all equivalent mathematical expressions
+    /// ### shortest first, the terms of every eclass are enumerated bottom-up
+    /// ### once and shared by all parents
+    /// #### Arguments
+    /// * `self`
+    /// * `grammar` - grammar from MathEGraph
+    /// * `init_exprs` - initial expressions
+    /// #### Return
+    /// * `None`
+    fn bottom_up_extract(&mut self, grammar: &Grammar, init_exprs: &[Math]) {
+        let mut enumerator = TermEnumerator::new(grammar)
+            .with_budget(self.round_budget.clone());
+
+        for n_tokens in 1..=self.token_limit as usize {
+            for init_expr in init_exprs {
+                for term in enumerator.terms_of(init_expr, n_tokens) {
+                    self.add_equiv_expr(&term.tokens());
+                    if self.target_reached { return; }
+                }
+                if enumerator.timed_out() {
+                    self.timed_out = true;
                     return;
                 }
-
-                log_trace(&format!("Back to Function Call {}\n", idx));
-                tokens = prev_tokens.clone();
             }
         }
-        if term { break; }
-    }
-    log_trace(&format!("Finish Function Call {}\n", idx));
-    log_trace("-----------------------------------\n");
 
-    return;
-}
+        return;
+    }
 
-/// ### public function to start extracting equivalent expressions
-/// #### Argument
-/// * `clis` command line arguments for hyperparameters
-/// * `skip_ecls` - skip eclasses
-/// * `grammar` - grammar
-/// * `init_exprs` - initial expressions
-/// #### Return
-/// * `None`
-pub fn extract(cli: &Vec<CliDtype>, skip_ecls: &HashMap<String, f64>, grammar: &HashMap<String, Vec<String>>, init_exprs: &Vec<String>) {
-    /* setup global variables */
-    unsafe {
-        if let CliDtype::Bool(optimized) = &cli[0] {
-            OPTIMIZED = *optimized;
-        }
-        if let CliDtype::UInt8(n_equiv_exprs) = &cli[1] {
-            N_EQUIV_EXPRS = *n_equiv_exprs;
-        }
-        if let CliDtype::UInt8(token_limit) = &cli[2] {
-            TOKEN_LIMIT = *token_limit;
-        }
-        if let CliDtype::UInt8(max_token_limit) = &cli[3] {
-            MAX_TOKEN_LIMIT = *max_token_limit;
-        }
-        if let CliDtype::UInt16(time_limit) = &cli[4] {
-            TIME_LIMIT = *time_limit;
+    /// ### private member function to sample equivalent mathematical expressions
+    /// ### uniformly at random from all expressions within the token limit,
+    /// ### every expression is picked with the same probability, samples are
+    /// ### drawn until there are enough distinct kept expressions (see
+    /// ### n_target and n_candidates), all expressions are drawn or
+    /// ### MAX_DRAWS_PER_TARGET draws per expression are spent
+    /// #### Arguments
+    /// * `self`
+    /// * `grammar` - grammar from MathEGraph
+    /// * `init_exprs` - initial expressions
+    /// #### Return
+    /// * `None`
+    fn sample_extract(&mut self, grammar: &Grammar, init_exprs: &[Math]) {
+        let max_len = self.token_limit as usize;
+        let term_counts = TermCounts::new(grammar, max_len);
+        let len_counts = term_counts.len_counts(init_exprs);
+        let total: BigUint = len_counts.iter().sum();
+        log_debug(&format!("Total # of expressions within {} tokens: {}\n", max_len, total));
+
+        /* seeded by token limit, a round samples the same expressions on every run */
+        let mut sampler = Sampler::new(self.get_seed() ^ self.token_limit as u64);
+        let n_target = self.n_target().unwrap_or(self.n_candidates());
+        /* filtered and duplicate (see Canonicalization) samples do not count,
+           but every draw does, so a round draws at most MAX_DRAWS_PER_TARGET * n_target times */
+        let max_draws = n_target.saturating_mul(MAX_DRAWS_PER_TARGET);
+        let mut sampled: HashSet<BigUint> = Default::default();
+        let mut keys: HashSet<String> = Default::default();
+        let mut n_draws: usize = 0;
+        while keys.len() < n_target && BigUint::from(sampled.len()) < total && n_draws < max_draws {
+            if self.round_budget.is_exhausted() {
+                self.timed_out = true;
+                return;
+            }
+            n_draws += 1;
+            let mut index = sampler.below(&total);
+            if !sampled.insert(index.clone()) { continue; }
+            let mut n_tokens = 0;
+            while index >= len_counts[n_tokens] {
+                index -= &len_counts[n_tokens];
+                n_tokens += 1;
+            }
+            let tokens = term_counts.unrank(init_exprs, n_tokens, &index);
+            if self.keep_expr(&tokens) {
+                keys.insert(self.get_canonicalization().key(&tokens_to_string(&tokens)));
+            }
+            self.add_equiv_expr(&tokens);
         }
-        SKIP_ECLS = Some(skip_ecls.clone());
-        GRAMMAR = Some(grammar.clone());
-        STATE = Some(Default::default());
-        EQUIV_EXPRS = Some(Default::default());
+
+        return;
     }
 
-    let init_token_exprs: Vec<Vec<String>> = init_exprs
-        .iter()
-        .map(|s| s.split_whitespace().map(|token| token.to_string()).collect())
-        .collect();
+    /// ### private member function to extract the `n_equiv_exprs` cheapest
+    /// ### equivalent mathematical expressions under a cost function,
+    /// ### expressions over the token limit are dropped, the extraction
+    /// ### stops once the budget of the round is exhausted
+    /// #### Arguments
+    /// * `self`
+    /// * `grammar` - grammar from MathEGraph
+    /// * `init_exprs` - initial expressions
+    /// * `cost_function` - cost function
+    /// #### Return
+    /// * `None`
+    fn top_k_extract<CF: CostFunction<Math>>(&mut self, grammar: &Grammar, init_exprs: &[Math], cost_function: CF) {
+        let classes = grammar.iter().map(|(eclass, enodes)| (eclass, enodes.to_vec()));
+        /* AC duplicates are removed afterwards, so k doubles every round */
+        let k = self.get_n_equiv_exprs().saturating_mul(1 << self.get_stats().rounds.len().min(16));
+        let mut extractor = KBestExtractor::from_classes_with_budget(classes, cost_function, k, self.round_budget.clone());
+
+        for (cost, expr) in extractor.find_k_best_of(init_exprs) {
+            let tokens = recexpr_to_tokens(&expr);
+            log_trace_raw(&format!("[COST]:  {:?}\n", cost));
+            if tokens.len() > self.token_limit as usize { continue; }
+            self.add_equiv_expr(&tokens);
+        }
+        if extractor.timed_out() { self.timed_out = true; }
+
+        return;
+    }
 
-    unsafe {
-        START_TIME = Some(Instant::now());
+    /// ### member function to start extracting equivalent expressions
+    /// #### Argument
+    /// * `self`
+    /// * `init_exprs` - initial expressions
+    /// #### Return
+    /// * `None`
+    pub fn extract(&mut self, init_exprs: &[Math]) {
+        /* reset extraction states */
+        self.state = Default::default();
+        self.equiv_exprs = Default::default();
+        self.timed_out = false;
+        self.canonical_keys = Default::default();
+        self.target_reached = false;
+
+        let init_token_exprs: Vec<Vec<GrammarToken>> = init_exprs.iter().map(production_tokens).collect();
+
+        /* take grammar out of self to traverse it while recording states */
+        let grammar = std::mem::take(&mut self.grammar);
+        self.round_budget = self.get_budget().sub_budget(Duration::from_secs(self.time_limit as u64));
         /* start extraction */
-        if OPTIMIZED {
+        if self.get_extraction() == ExtractionKind::TopK {
+            match self.get_cost() {
+                CostKind::Size => { self.top_k_extract(&grammar, init_exprs, AstSize) },
+                CostKind::Operator => { self.top_k_extract(&grammar, init_exprs, OperatorCost::default()) },
+                CostKind::Trig => { self.top_k_extract(&grammar, init_exprs, TrigCount) },
+                CostKind::Depth => { self.top_k_extract(&grammar, init_exprs, DepthPenalizedSize::default()) },
+            }
+        } else if self.get_extraction() == ExtractionKind::BottomUp {
+            self.bottom_up_extract(&grammar, init_exprs);
+        } else if self.get_extraction() == ExtractionKind::Sample {
+            self.sample_extract(&grammar, init_exprs);
+        } else if self.get_extraction() == ExtractionKind::Shortest {
+            let filters = std::mem::take(&mut self.filters);
+            let mut iter = EquivExprIter::new(&grammar, &filters, init_exprs, self.token_limit as usize)
+                .with_budget(self.round_budget.clone());
+            for tokens in &mut iter {
+                /* the iterator applies the filters */
+                let final_expr = tokens_to_string(&tokens);
+                log_trace_raw(&format!("[FINAL]: {}\n", final_expr));
+                self.insert_equiv_expr(final_expr);
+                if self.target_reached { break; }
+            }
+            self.timed_out = iter.timed_out();
+            self.filters = filters;
+        } else if self.get_optimized() {
             for init_token_expr in init_token_exprs {
                 // if init_token_expr[0] != "d" && !skip_rw(&init_token_expr) {
-                    optimized_extract(init_token_expr, 0);
+                    self.optimized_extract(&grammar, init_token_expr, 0);
                 // } else {
                 //     println!("skip!!!");
                 // }
             }
         } else {
             for init_token_expr in init_token_exprs {
-                exhaustive_extract(init_token_expr, 0);
+                self.exhaustive_extract(&grammar, init_token_expr, 0);
             }
         }
-    }
+        self.grammar = grammar;
 
-    return;
+        return;
+    }
 }
@@ -0,0 +1,292 @@
+use crate::*;
+
+/// Derivation Frame Struct
+/// a sentential form of the leftmost derivation and
+/// the next production of its leftmost eclass to try
+struct Frame {
+    /// tokens of the sentential form
+    tokens: Vec<GrammarToken>,
+    /// index of the leftmost eclass
+    i: usize,
+    /// leftmost eclass
+    op: Id,
+    /// minimum # of tokens of any expression derived from tokens
+    min_len: usize,
+    /// index of the next production of the leftmost eclass
+    next_rw: usize,
+}
+
+/// Equivalent Expression Iterator Struct
+/// yield the equivalent expressions of a root eclass lazily,
+/// all expressions of k tokens before any expression of k+1 tokens,
+/// only the current derivation path is held in memory
+pub struct EquivExprIter<'a> {
+    /// grammar from MathEGraph
+    grammar: &'a Grammar,
+    /// filters of the sentential forms and expressions
+    filters: &'a [Box<dyn ExprFilter>],
+    /// minimum # of tokens of every eclass
+    min_lens: HashMap<Id, usize>,
+    /// initial expressions of the root eclass
+    init_exprs: Vec<Math>,
+    /// token limit
+    token_limit: usize,
+    /// # of tokens of the expressions being derived
+    n_tokens: usize,
+    /// index of the next initial expression
+    next_init_expr: usize,
+    /// leftmost derivation path
+    stack: Vec<Frame>,
+    /// budget of the extraction
+    budget: Budget,
+    /// whether the budget is exhausted
+    timed_out: bool,
+}
+
+impl<'a> EquivExprIter<'a> {
+    /// ### constructor
+    /// #### Arguments
+    /// * `grammar` - grammar from MathEGraph
+    /// * `filters` - filters of the sentential forms and expressions
+    /// * `init_exprs` - initial expressions of the root eclass
+    /// * `token_limit` - token limit
+    /// #### Return
+    /// * `EquivExprIter`
+    pub fn new(grammar: &'a Grammar, filters: &'a [Box<dyn ExprFilter>], init_exprs: &[Math], token_limit: usize) -> Self {
+        return EquivExprIter {
+            grammar,
+            filters,
+            min_lens: grammar.min_lens(),
+            init_exprs: init_exprs.to_vec(),
+            token_limit,
+            n_tokens: 1,
+            next_init_expr: 0,
+            stack: vec![],
+            budget: Default::default(),
+            timed_out: false,
+        };
+    }
+
+    /// ### member function to stop the iteration once the budget
+    /// ### is exhausted (out of time or cancelled)
+    /// #### Arguments
+    /// * `self`
+    /// * `budget` - budget of the extraction
+    /// #### Return
+    /// * `EquivExprIter`
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = budget;
+        return self;
+    }
+
+    /// ### member function to check if the budget is exhausted
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `bool` - whether the budget is exhausted or not
+    pub fn timed_out(&self) -> bool { return self.timed_out; }
+
+    /// ### private member function to start deriving the next initial expression,
+    /// ### moving on to 1 more token after the last initial expression
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `Option` - the initial expression if it is complete already,
+    ///   None if it is pushed for derivation or skipped
+    fn start_next(&mut self) -> Option<Vec<GrammarToken>> {
+        if self.next_init_expr == self.init_exprs.len() {
+            self.next_init_expr = 0;
+            self.n_tokens += 1;
+            return None;
+        }
+        let init_expr = &self.init_exprs[self.next_init_expr];
+        self.next_init_expr += 1;
+
+        let min_len = production_min_len(&self.min_lens, init_expr)?;
+        if min_len > self.n_tokens { return None; }
+        let tokens = production_tokens(init_expr);
+        if !self.filters.iter().all(|filter| filter.keep_partial(&tokens)) { return None; }
+
+        return self.push_or_complete(tokens, 0, min_len);
+    }
+
+    /// ### private member function to push a sentential form for derivation,
+    /// ### or return it if it has no eclass left
+    /// #### Arguments
+    /// * `self`
+    /// * `tokens` - tokens of the sentential form
+    /// * `from` - index to search for the leftmost eclass from
+    /// * `min_len` - minimum # of tokens of any expression derived from tokens
+    /// #### Return
+    /// * `Option` - the complete expression of exactly `n_tokens` tokens, or None
+    fn push_or_complete(&mut self, tokens: Vec<GrammarToken>, from: usize, min_len: usize) -> Option<Vec<GrammarToken>> {
+        let leftmost = tokens[from..].iter().enumerate().find_map(|(i, token)| match token {
+            GrammarToken::NonTerminal(eclass) => { Some((from + i, *eclass)) },
+            GrammarToken::Terminal(_) => { None },
+        });
+
+        match leftmost {
+            Some((i, op)) => {
+                self.stack.push(Frame { tokens, i, op, min_len, next_rw: 0 });
+                return None;
+            },
+            None => {
+                if tokens.len() == self.n_tokens && self.filters.iter().all(|filter| filter.keep_expr(&tokens)) {
+                    return Some(tokens);
+                }
+                return None;
+            },
+        }
+    }
+}
+
+impl Iterator for EquivExprIter<'_> {
+    type Item = Vec<GrammarToken>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.n_tokens <= self.token_limit && !self.timed_out {
+            if self.budget.is_exhausted() {
+                self.timed_out = true;
+                return None;
+            }
+
+            let frame = match self.stack.last_mut() {
+                Some(frame) => { frame },
+                None => {
+                    if let Some(tokens) = self.start_next() { return Some(tokens); }
+                    continue;
+                },
+            };
+            let rw = match self.grammar.productions(frame.op).get(frame.next_rw) {
+                Some(rw) => { rw },
+                None => {
+                    self.stack.pop();
+                    continue;
+                },
+            };
+            frame.next_rw += 1;
+
+            let rw_len = match production_min_len(&self.min_lens, rw) {
+                Some(rw_len) => { rw_len },
+                None => { continue; },
+            };
+            let min_len = frame.min_len - self.min_lens[&frame.op] + rw_len;
+            if min_len > self.n_tokens { continue; }
+
+            let (i, mut tokens) = (frame.i, frame.tokens.clone());
+            tokens.splice(i..i+1, production_tokens(rw));
+            if !self.filters.iter().all(|filter| filter.keep_partial(&tokens)) { continue; }
+            if let Some(tokens) = self.push_or_complete(tokens, i, min_len) { return Some(tokens); }
+        }
+
+        return None;
+    }
+}
+
+/// ### function to convert tokens of a complete expression
+/// ### in prefix notation into a RecExpr
+/// #### Argument
+/// * `tokens` - tokens without eclass(es)
+/// #### Return
+/// * `RecExpr<Math>` - expression
+pub fn tokens_to_recexpr(tokens: &[GrammarToken]) -> RecExpr<Math> {
+    /// ### function to add the subexpression starting at a token
+    /// ### returning its root and the index after it
+    fn add(tokens: &[GrammarToken], i: usize, expr: &mut RecExpr<Math>) -> (Id, usize) {
+        let enode = match &tokens[i] {
+            GrammarToken::Terminal(enode) => { enode.clone() },
+            GrammarToken::NonTerminal(eclass) => { panic!("incomplete expression with eclass e{}", eclass); },
+        };
+        let mut next = i + 1;
+        let enode = enode.map_children(|_| {
+            let (child, after) = add(tokens, next, expr);
+            next = after;
+            return child;
+        });
+
+        return (expr.add(enode), next);
+    }
+
+    let mut expr = RecExpr::default();
+    add(tokens, 0, &mut expr);
+
+    return expr;
+}
+
+/// ### function to convert an expression into tokens in prefix notation,
+/// ### the inverse of tokens_to_recexpr
+/// #### Argument
+/// * `expr` - expression
+/// #### Return
+/// * `Vec<GrammarToken>` - tokens without eclass(es)
+pub fn recexpr_to_tokens(expr: &RecExpr<Math>) -> Vec<GrammarToken> {
+    /// ### function to append the tokens of the subexpression at a node
+    fn add(expr: &RecExpr<Math>, id: Id, tokens: &mut Vec<GrammarToken>) {
+        tokens.push(GrammarToken::Terminal(expr[id].clone()));
+        for child in expr[id].children() {
+            add(expr, *child, tokens);
+        }
+    }
+
+    let mut tokens = vec![];
+    add(expr, expr.root(), &mut tokens);
+
+    return tokens;
+}
+
+/// ### function to parse an expression in prefix notation (e.g. from
+/// ### tokens_to_string) into a RecExpr, every operator takes the
+/// ### largest arity it is defined with
+/// #### Argument
+/// * `expr` - expression in prefix notation, e.g. "+ x * 2 y"
+/// #### Return
+/// * `Result` - expression, or why it cannot be parsed
+pub fn prefix_to_recexpr(expr: &str) -> Result<RecExpr<Math>, String> {
+    /// ### function to add the subexpression starting at the next token
+    fn add<'a>(tokens: &mut impl Iterator<Item = &'a str>, expr: &mut RecExpr<Math>) -> Result<Id, String> {
+        let token = tokens.next().ok_or("incomplete expression")?;
+        for arity in [2, 1] {
+            if Math::from_op(token, vec![Id::from(0); arity]).is_ok() {
+                let children = (0..arity).map(|_| add(tokens, expr)).collect::<Result<Vec<Id>, String>>()?;
+                let enode = Math::from_op(token, children).map_err(|e| format!("{:?}", e))?;
+                return Ok(expr.add(enode));
+            }
+        }
+        let leaf = Math::from_op(token, vec![]).map_err(|e| format!("{:?}", e))?;
+
+        return Ok(expr.add(leaf));
+    }
+
+    let mut tokens = expr.split_whitespace();
+    let mut recexpr = RecExpr::default();
+    add(&mut tokens, &mut recexpr)?;
+    if let Some(token) = tokens.next() {
+        return Err(format!("unexpected token '{}' after the expression '{}'", token, expr));
+    }
+
+    return Ok(recexpr);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lazy_equiv_exprs() {
+        let mut generator = Generator::new(false, usize::MAX, 5, 5, 60).with_extraction(ExtractionKind::Shortest);
+        generator.generate_exprs("(sin x)").unwrap();
+        let equiv_exprs = generator.get_equiv_exprs().clone();
+
+        let iter = generator.iter_equiv_exprs("(sin x)").unwrap();
+        let lens: Vec<usize> = iter.map(|tokens| tokens.len()).collect();
+        assert!(lens.windows(2).all(|lens| lens[0] <= lens[1]));
+        assert_eq!(lens.len(), equiv_exprs.len());
+
+        let first: Vec<RecExpr<Math>> = generator.iter_equiv_exprs("(sin x)").unwrap().take(1).map(|tokens| tokens_to_recexpr(&tokens)).collect();
+        assert_eq!(first, vec!["(sin x)".parse().unwrap()]);
+
+        let expr: RecExpr<Math> = "(+ (d x (sin x)) (* 2 y))".parse().unwrap();
+        assert_eq!(prefix_to_recexpr(&tokens_to_string(&recexpr_to_tokens(&expr))), Ok(expr));
+        assert!(prefix_to_recexpr("+ x").is_err() && prefix_to_recexpr("sin x y").is_err());
+    }
+}
@@ -0,0 +1,300 @@
+use crate::*;
+
+/// Expression Filter Trait
+/// predicates to prune the extraction, every predicate keeps
+/// everything by default, a filter overrides the ones it needs
+pub trait ExprFilter {
+    /// ### member function to check if a production is kept in the grammar,
+    /// ### pruned productions are never used by any extraction
+    /// #### Arguments
+    /// * `self`
+    /// * `grammar` - grammar from MathEGraph, before pruning
+    /// * `enode` - production
+    /// #### Return
+    /// * `bool` - whether the production is kept or not
+    fn keep_production(&self, _grammar: &Grammar, _enode: &Math) -> bool { return true; }
+
+    /// ### member function to check if a sentential form is derived further,
+    /// ### false only if no expression derived from it can be kept
+    /// #### Arguments
+    /// * `self`
+    /// * `tokens` - tokens of the sentential form, with eclass(es)
+    /// #### Return
+    /// * `bool` - whether the sentential form is kept or not
+    fn keep_partial(&self, _tokens: &[GrammarToken]) -> bool { return true; }
+
+    /// ### member function to check if an extracted expression is kept
+    /// #### Arguments
+    /// * `self`
+    /// * `tokens` - tokens of the expression in prefix notation, without eclass(es)
+    /// #### Return
+    /// * `bool` - whether the expression is kept or not
+    fn keep_expr(&self, _tokens: &[GrammarToken]) -> bool { return true; }
+}
+
+/// Identity Filter Struct
+/// prune productions applying an identity element,
+/// `* eN`, `pow eN` or `/ _ eN` when eclass N is the constant 1,
+/// `+ eN` or `- _ eN` when eclass N is the constant 0
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IdentityFilter;
+
+impl ExprFilter for IdentityFilter {
+    fn keep_production(&self, grammar: &Grammar, enode: &Math) -> bool {
+        let is = |eclass: &Id, constant: f64| {
+            return matches!(grammar.productions(*eclass), [Math::Constant(c)] if c.into_inner() == constant);
+        };
+
+        return !match enode {
+            Math::Mul([a, b]) | Math::Pow([a, b]) => { is(a, 1.0) || is(b, 1.0) },
+            Math::Div([_, b]) => { is(b, 1.0) },
+            Math::Add([a, b]) => { is(a, 0.0) || is(b, 0.0) },
+            Math::Sub([_, b]) => { is(b, 0.0) },
+            _ => { false },
+        };
+    }
+}
+
+/// Operator Filter Struct
+/// forbid an operator (or leaf) in the extracted expressions
+#[derive(Clone, Debug)]
+pub struct OpFilter {
+    /// forbidden operator, e.g. "d"
+    pub op: String,
+}
+
+impl ExprFilter for OpFilter {
+    fn keep_production(&self, _grammar: &Grammar, enode: &Math) -> bool {
+        return enode.to_string() != self.op;
+    }
+
+    fn keep_partial(&self, tokens: &[GrammarToken]) -> bool {
+        return self.keep_expr(tokens);
+    }
+
+    fn keep_expr(&self, tokens: &[GrammarToken]) -> bool {
+        return !tokens.iter().any(|token| match token {
+            GrammarToken::Terminal(enode) => { enode.to_string() == self.op },
+            GrammarToken::NonTerminal(_) => { false },
+        });
+    }
+}
+
+/// Derivative-Free Filter Struct
+/// forbid unevaluated derivatives and integrals (`d` and `i`)
+/// in the extracted expressions, the derivative eclasses are
+/// resolved through their derivative-free productions only
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DerivativeFreeFilter;
+
+impl DerivativeFreeFilter {
+    /// ### private function to check if a token is a derivative or an integral
+    /// #### Argument
+    /// * `token` - token
+    /// #### Return
+    /// * `bool` - whether the token is a derivative or an integral or not
+    fn is_derivative(token: &GrammarToken) -> bool {
+        return matches!(token, GrammarToken::Terminal(Math::Diff(_) | Math::Integral(_)));
+    }
+}
+
+impl ExprFilter for DerivativeFreeFilter {
+    fn keep_production(&self, _grammar: &Grammar, enode: &Math) -> bool {
+        return !matches!(enode, Math::Diff(_) | Math::Integral(_));
+    }
+
+    fn keep_partial(&self, tokens: &[GrammarToken]) -> bool {
+        return self.keep_expr(tokens);
+    }
+
+    fn keep_expr(&self, tokens: &[GrammarToken]) -> bool {
+        return !tokens.iter().any(DerivativeFreeFilter::is_derivative);
+    }
+}
+
+/// Pattern Filter Struct
+/// forbid a pattern anywhere in the extracted expressions,
+/// e.g. `(pow ?a 1)` or `(* -1 (* -1 ?a))`
+#[derive(Clone, Debug)]
+pub struct PatternFilter {
+    /// forbidden pattern
+    pub pattern: PatternAst<Math>,
+}
+
+impl PatternFilter {
+    /// ### private member function to match the pattern at a node of an expression
+    /// #### Arguments
+    /// * `self`
+    /// * `pat_id` - node of the pattern
+    /// * `expr` - expression
+    /// * `id` - node of the expression
+    /// * `subst` - expression node of every bound pattern variable
+    /// #### Return
+    /// * `bool` - whether the pattern matches or not
+    fn matches(&self, pat_id: Id, expr: &RecExpr<Math>, id: Id, subst: &mut HashMap<Var, Id>) -> bool {
+        return match &self.pattern[pat_id] {
+            ENodeOrVar::Var(var) => {
+                match subst.get(var) {
+                    Some(bound) => { same_subexpr(expr, *bound, id) },
+                    None => {
+                        subst.insert(*var, id);
+                        true
+                    },
+                }
+            },
+            ENodeOrVar::ENode(enode) => {
+                enode.matches(&expr[id]) &&
+                    enode.children().iter().zip(expr[id].children()).all(|(pat_child, child)| {
+                        return self.matches(*pat_child, expr, *child, subst);
+                    })
+            },
+        };
+    }
+}
+
+impl ExprFilter for PatternFilter {
+    fn keep_expr(&self, tokens: &[GrammarToken]) -> bool {
+        let expr = tokens_to_recexpr(tokens);
+        let root = self.pattern.root();
+
+        return !(0..expr.as_ref().len()).any(|i| self.matches(root, &expr, Id::from(i), &mut Default::default()));
+    }
+}
+
+/// Depth Filter Struct
+/// cap the AST depth of the extracted expressions
+#[derive(Clone, Copy, Debug)]
+pub struct DepthFilter {
+    /// maximum AST depth, a leaf has depth 1
+    pub max_depth: usize,
+}
+
+impl ExprFilter for DepthFilter {
+    fn keep_partial(&self, tokens: &[GrammarToken]) -> bool {
+        /* an eclass adds at least 1 level */
+        return prefix_depth(tokens) <= self.max_depth;
+    }
+
+    fn keep_expr(&self, tokens: &[GrammarToken]) -> bool {
+        return prefix_depth(tokens) <= self.max_depth;
+    }
+}
+
+/// ### function to check if 2 subexpressions of an expression are the same
+/// #### Arguments
+/// * `expr` - expression
+/// * `lhs` - root of 1st subexpression
+/// * `rhs` - root of 2nd subexpression
+/// #### Return
+/// * `bool` - whether the subexpressions are the same or not
+fn same_subexpr(expr: &RecExpr<Math>, lhs: Id, rhs: Id) -> bool {
+    return lhs == rhs || (
+        expr[lhs].matches(&expr[rhs]) &&
+            expr[lhs].children().iter().zip(expr[rhs].children()).all(|(lhs, rhs)| same_subexpr(expr, *lhs, *rhs))
+    );
+}
+
+/// ### function to compute the AST depth of tokens in prefix notation,
+/// ### an eclass counts as a leaf
+/// #### Argument
+/// * `tokens` - tokens (expression)
+/// #### Return
+/// * `usize` - AST depth
+pub fn prefix_depth(tokens: &[GrammarToken]) -> usize {
+    /* # of children left of every open operator */
+    let mut open: Vec<usize> = vec![];
+    let mut depth = 0;
+
+    for token in tokens {
+        depth = depth.max(open.len() + 1);
+        let arity = match token {
+            GrammarToken::Terminal(enode) => { enode.len() },
+            GrammarToken::NonTerminal(_) => { 0 },
+        };
+        if arity > 0 {
+            open.push(arity);
+            continue;
+        }
+        while let Some(n_children) = open.last_mut() {
+            *n_children -= 1;
+            if *n_children > 0 { break; }
+            open.pop();
+        }
+    }
+
+    return depth;
+}
+
+/// ### function to parse a filter from its spec,
+/// ### `identity`, `derivative_free`, `op:<op>`, `pattern:<pattern>`
+/// ### or `depth:<max depth>`
+/// #### Argument
+/// * `spec` - filter spec, e.g. "op:d" or "pattern:(pow ?a 1)"
+/// #### Return
+/// * `Result` - filter, or why the spec is invalid
+pub fn parse_filter(spec: &str) -> Result<Box<dyn ExprFilter>, String> {
+    let (kind, arg) = match spec.split_once(':') {
+        Some((kind, arg)) => { (kind.trim(), arg.trim()) },
+        None => { (spec.trim(), "") },
+    };
+
+    return match (kind, arg) {
+        ("identity", "") => { Ok(Box::new(IdentityFilter)) },
+        ("derivative_free", "") => { Ok(Box::new(DerivativeFreeFilter)) },
+        ("op", op) if !op.is_empty() => { Ok(Box::new(OpFilter { op: op.to_string() })) },
+        ("pattern", pattern) => {
+            match pattern.parse::<Pattern<Math>>() {
+                Ok(pattern) => { Ok(Box::new(PatternFilter { pattern: pattern.ast })) },
+                Err(e) => { Err(format!("invalid pattern '{}' of filter '{}', {}", pattern, spec, e)) },
+            }
+        },
+        ("depth", max_depth) => {
+            match max_depth.parse::<usize>() {
+                Ok(max_depth) if max_depth > 0 => { Ok(Box::new(DepthFilter { max_depth })) },
+                _ => { Err(format!("invalid max depth '{}' of filter '{}', expect usize > 0", max_depth, spec)) },
+            }
+        },
+        _ => { Err(format!("invalid filter '{}', expect identity, derivative_free, op:<op>, pattern:<pattern> or depth:<max depth>", spec)) },
+    };
+}
+
+/// ### function to get the default filters,
+/// ### the identity filter if meaningless rewrites are suppressed
+/// #### Argument
+/// * `None`
+/// #### Return
+/// * `Vec<Box<dyn ExprFilter>>` - default filters
+pub fn default_filters() -> Vec<Box<dyn ExprFilter>> {
+    if SUPPRESS { return vec![Box::new(IdentityFilter)]; }
+
+    return vec![];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expr_filters() {
+        let tokens = |expr: &str| { return recexpr_to_tokens(&expr.parse().unwrap()); };
+        let double_neg = parse_filter("pattern:(* -1 (* -1 ?a))").unwrap();
+        assert!(!double_neg.keep_expr(&tokens("(+ 1 (* -1 (* -1 x)))")));
+        assert!(double_neg.keep_expr(&tokens("(* -1 (* 2 x))")));
+        let same = parse_filter("pattern:(- ?a ?a)").unwrap();
+        assert!(!same.keep_expr(&tokens("(- (sin x) (sin x))")) && same.keep_expr(&tokens("(- x y)")));
+
+        assert!(!parse_filter("op:d").unwrap().keep_expr(&tokens("(+ (d x x) 1)")));
+        let derivative_free = parse_filter("derivative_free").unwrap();
+        assert!(!derivative_free.keep_expr(&tokens("(+ (d x x) 1)")) && !derivative_free.keep_expr(&tokens("(i x x)")));
+        assert!(derivative_free.keep_expr(&tokens("(cos x)")));
+        assert_eq!(prefix_depth(&tokens("(+ (sin (cos x)) 1)")), 4);
+        assert!(!parse_filter("depth:3").unwrap().keep_expr(&tokens("(+ (sin (cos x)) 1)")));
+        assert!(parse_filter("depth:0").is_err() && parse_filter("pow").is_err());
+
+        /* sin e0 has depth ≥ 2 */
+        let mut partial = tokens("(sin x)");
+        partial[1] = GrammarToken::NonTerminal(Id::from(0));
+        assert!(!parse_filter("depth:1").unwrap().keep_partial(&partial));
+        assert!(parse_filter("depth:2").unwrap().keep_partial(&partial));
+    }
+}
@@ -1,166 +1,510 @@
 use crate::*;
 use quanta::Instant;
+use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::panic::{self, AssertUnwindSafe};
 use std::process::exit;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Condvar, Mutex};
+use std::thread;
 
-/// ### private function generate equivalent expressions
-/// ### with 1 input expression
+/// max # of finished expressions per worker waiting to be written in input order
+const PENDING_PER_JOB: usize = 2;
+
+/// ### private function to write 1 block of equivalent expressions
+/// ### (input expression, equivalent expressions, blank line) into output file
+/// #### Arguments
+/// * `writer` - buffered writer of the output file
+/// * `output_file` - output file
+/// * `input_expr` - input expression
+/// * `equiv_exprs` - equivalent expressions of the input expression
+/// #### Return
+/// * `None`
+fn write_exprs(writer: &mut BufWriter<&File>, output_file: &File, input_expr: &str, equiv_exprs: &HashSet<String>) {
+    match writeln!(writer, "{}", &input_expr.replace(|c| c == '(' || c == ')', "")) {
+        Ok(_) => {},
+        Err(e) => {
+            log_error(&format!("Failed to write input expr '{}' into output file '{:?}'.\n", input_expr, output_file));
+            log_error(&format!("{}\n", e));
+            exit(1);
+        },
+    };
+
+    /* write equivalent expressions into output file */
+    for expr in sort_exprs(equiv_exprs) {
+        match writeln!(writer, "{}", expr) {
+            Ok(_) => {},
+            Err(e) => {
+                log_error(&format!("Failed to write expr '{}' into output file '{:?}'.\n", expr, output_file));
+                log_error(&format!("{}\n", e));
+                exit(1);
+            },
+        };
+    }
+    match writeln!(writer, "") {
+        Ok(_) => {},
+        Err(e) => {
+            log_error(&format!("Failed to write '' into output file '{:?}'.\n", output_file));
+            log_error(&format!("{}\n", e));
+            exit(1);
+        },
+    };
+
+    /* flush the output stream */
+    match writer.flush() {
+        Ok(_) => {},
+        Err(e) => {
+            log_error(&format!("Failed to flush buffer to output file '{:?}'.\n", output_file));
+            log_error(&format!("{}\n", e));
+            exit(1);
+        },
+    }
+
+    return;
+}
+
+/// ### private function to write 1 JSON object of equivalent expressions
+/// ### (input expression, equivalent expressions, statistics) into output file
+/// #### Arguments
+/// * `writer` - buffered writer of the output file
+/// * `output_file` - output file
+/// * `input_expr` - input expression
+/// * `equiv_exprs` - equivalent expressions of the input expression
+/// * `stats` - statistics of the generation
+/// * `deterministic` - leave out the wall-clock times of the statistics
+/// #### Return
+/// * `None`
+fn write_jsonl(writer: &mut BufWriter<&File>, output_file: &File, input_expr: &str, equiv_exprs: &HashSet<String>, stats: &ExprStats, deterministic: bool) {
+    let stats = match deterministic {
+        true => { stats.to_untimed_json() },
+        false => { stats.to_json() },
+    };
+    let record = serde_json::json!({
+        "input": input_expr,
+        "equiv_exprs": sort_exprs(equiv_exprs),
+        "stats": stats,
+    });
+    match writeln!(writer, "{}", record) {
+        Ok(_) => {},
+        Err(e) => {
+            log_error(&format!("Failed to write JSON object of input expr '{}' into output file '{:?}'.\n", input_expr, output_file));
+            log_error(&format!("{}\n", e));
+            exit(1);
+        },
+    };
+
+    /* flush the output stream */
+    match writer.flush() {
+        Ok(_) => {},
+        Err(e) => {
+            log_error(&format!("Failed to flush buffer to output file '{:?}'.\n", output_file));
+            log_error(&format!("{}\n", e));
+            exit(1);
+        },
+    }
+
+    return;
+}
+
+/// ### private function to intern the symbols of the rewrite rules and of
+/// ### all input expressions in input order, symbol ids (and so hashing
+/// ### and enode order) are then the same on every run whatever the
+/// ### number of workers or the resume point
 /// #### Argument
-/// * `cli` - pre-processed command line arguments
+/// * `input_exprs` - all input expressions
 /// #### Return
-/// * `equiv_expr` - Vec<String> of equivalent expressions
-fn generate_exprs(mut cli: Vec<CliDtype>) -> HashSet<String> {
-    /* initialize ctx_gr struct and create egraph, skip_ecls, grammar, init_rewrite */
-    let input_expr = cli[5].to_string();
-    log_info(&format!("Expression: {}\n", input_expr));
-    let mut ctx_gr = ContextGrammar::new(input_expr);
-    ctx_gr.setup();
-    pt_egraph_info(&ctx_gr.egraph);
-    let skip_ecls = &ctx_gr.skip_eclasses.clone();
-    let grammar = &ctx_gr.grammar.clone();
-    let init_exprs = &ctx_gr.init_exprs.clone();
-    log_info(&format!("Total # of init expr(s): {}\n", init_exprs.len()));
-
-    #[allow(unused_assignments)]
-    let mut equiv_exprs: HashSet<String> = HashSet::default();
-
-    loop {
-        let start_time = Instant::now();
-        extract(&cli, skip_ecls, grammar, init_exprs);
-        let end_time = Instant::now();
-        let elapsed_time = end_time.duration_since(start_time).as_secs();
-        log_info(&format!("Expression extraction time: {}s\n", elapsed_time));
-
-        unsafe {
-            let start_time = Instant::now();
-            equiv_exprs = get_global_equiv_exprs().clone();
-            let orig_num_exprs = equiv_exprs.len();
-            /* post-processing equivalent expressions */
-            equiv_exprs = rm_permu(&equiv_exprs);
-            let end_time = Instant::now();
-            let elapsed_time = end_time.duration_since(start_time).as_secs();
-            let num_exprs = equiv_exprs.len();
-            log_info(&format!("Expression postprocessing time: {}s\n", elapsed_time));
-            log_info(&format!("Total # of expression(s) before postprocessing: {}\n", orig_num_exprs));
-            log_info(&format!("Total # of expression(s) after  postprocessing: {}\n", num_exprs));
-
-            if num_exprs >= N_EQUIV_EXPRS as usize {
-                break;
-            }
+/// * `None`
+fn intern_symbols(input_exprs: &[String]) {
+    math_rule();
+    for input_expr in input_exprs {
+        /* parse errors are reported by the workers */
+        let _ = input_expr.parse::<RecExpr<Math>>();
+    }
 
-            log_info("-----------------------------------\n");
-            match cli[2] {
-                CliDtype::UInt8(ref mut token_limit) => {
-                    *token_limit += 1;
-                    if *token_limit > MAX_TOKEN_LIMIT {
-                        log_info(&format!("Token limit {} reaches max token limit {}.\n", token_limit, MAX_TOKEN_LIMIT));
-                        break;
-                    }
-                    log_info(&format!("Increase token limit to {}\n", token_limit));
-                },
-                _ => { log_error(&format!("Failed to convert '{:?}' to u8 datatype.\n", cli[3])); },
+    return;
+}
+
+/// ### private function to write 1 statistics record of an input expression
+/// ### into statistics file
+/// #### Arguments
+/// * `writer` - buffered writer of the statistics file
+/// * `stats_file` - statistics file
+/// * `line_num` - input line number, start from 1
+/// * `input_expr` - input expression
+/// * `stats` - statistics of the generation
+/// #### Return
+/// * `None`
+fn write_stats(writer: &mut BufWriter<&File>, stats_file: &File, line_num: usize, input_expr: &str, stats: &ExprStats) {
+    let mut record = stats.to_json();
+    record["line"] = serde_json::json!(line_num);
+    record["input"] = serde_json::json!(input_expr);
+    match writeln!(writer, "{}", record).and_then(|_| writer.flush()) {
+        Ok(_) => {},
+        Err(e) => {
+            log_error(&format!("Failed to write statistics of input expr '{}' into statistics file '{:?}'.\n", input_expr, stats_file));
+            log_error(&format!("{}\n", e));
+            exit(1);
+        },
+    };
+
+    return;
+}
+
+/// ### private function to write 1 failed input expression
+/// ### into output file (jsonl) or error log (txt)
+/// #### Arguments
+/// * `writer` - buffered writer of the output file or error log
+/// * `file` - output file or error log
+/// * `jsonl` - JSONL output format flag
+/// * `line_num` - input line number, start from 1
+/// * `input_expr` - input expression
+/// * `error` - reason the generation failed
+/// #### Return
+/// * `None`
+fn write_failure(writer: &mut BufWriter<&File>, file: &File, jsonl: bool, line_num: usize, input_expr: &str, error: &GenError) {
+    let record = match jsonl {
+        true => {
+            serde_json::json!({
+                "line": line_num,
+                "input": input_expr,
+                "failed": true,
+                "error": error.to_string(),
+            }).to_string()
+        },
+        /* one failure per line, multi-line panic messages are joined */
+        false => { format!("{}\t{}\t{}", line_num, input_expr, error.to_string().lines().collect::<Vec<_>>().join(" ")) },
+    };
+    match writeln!(writer, "{}", record).and_then(|_| writer.flush()) {
+        Ok(_) => {},
+        Err(e) => {
+            log_error(&format!("Failed to write failure of input expr '{}' into file '{:?}'.\n", input_expr, file));
+            log_error(&format!("{}\n", e));
+            exit(1);
+        },
+    };
+
+    return;
+}
+
+/// ### private function to cut a file back to its checkpoint offset when
+/// ### resuming, dropping the partial records written after the last
+/// ### finished expression, and to write from there
+/// #### Arguments
+/// * `file` - output file, statistics file or error log
+/// * `filepath` - filepath of the file
+/// * `offset` - checkpoint offset in bytes
+/// #### Return
+/// * `None`
+fn truncate_to_offset(mut file: &File, filepath: &str, offset: u64) {
+    match file.metadata() {
+        Ok(metadata) => {
+            if metadata.len() < offset {
+                log_error(&format!("File '{}' is shorter than checkpoint offset {}.\n", filepath, offset));
+                exit(1);
             }
-            match cli[4] {
-                CliDtype::UInt16(ref mut time_limit) => {
-                    *time_limit += 300;
-                    log_info(&format!("Increase time limit to {}\n", time_limit));
-                },
-                _ => { log_error(&format!("Failed to convert '{:?}' to u16 datatype.\n", cli[4])); },
+        },
+        Err(e) => {
+            log_error(&format!("Failed to read metadata of file '{}'.\n", filepath));
+            log_error(&format!("{}\n", e));
+            exit(1);
+        },
+    }
+    match file.set_len(offset).and_then(|_| file.seek(SeekFrom::Start(offset))) {
+        Ok(_) => {},
+        Err(e) => {
+            log_error(&format!("Failed to truncate file '{}' to checkpoint offset {}.\n", filepath, offset));
+            log_error(&format!("{}\n", e));
+            exit(1);
+        },
+    }
+
+    return;
+}
+
+/// ### private function to flush a buffered writer and sync its file,
+/// ### so the bytes before the returned offset are on disk
+/// #### Arguments
+/// * `writer` - buffered writer of the file
+/// * `file` - output file, statistics file or error log
+/// #### Return
+/// * `Result` - file offset in bytes, or io error
+fn synced_offset(writer: &mut BufWriter<&File>, file: &File) -> std::io::Result<u64> {
+    writer.flush()?;
+    file.sync_data()?;
+
+    return writer.stream_position();
+}
+
+/// ### private function to echo the effective generation config
+/// ### into `<output filepath>.config.json`, the file can be loaded
+/// ### back with `-c` to reproduce the run
+/// #### Arguments
+/// * `config` - generation config
+/// * `output_filepath` - output filepath
+/// #### Return
+/// * `None`
+fn write_config(config: &GenerationConfig, output_filepath: &str) {
+    let config_filepath = format!("{}.config.json", output_filepath);
+
+    /* a resumed run is expected to use the config of the interrupted run */
+    if config.resume {
+        if let Ok(prev_config) = GenerationConfig::from_file(&config_filepath) {
+            if (GenerationConfig { resume: true, ..prev_config }) != *config {
+                log_warn(&format!("Config differs from the config of the resumed run '{}'.\n", config_filepath));
             }
         }
     }
 
-    return equiv_exprs;
+    let content = serde_json::to_string_pretty(&config.to_json()).unwrap();
+    match std::fs::write(&config_filepath, content + "\n") {
+        Ok(_) => {},
+        Err(e) => {
+            log_error(&format!("Failed to write config file '{}'.\n", config_filepath));
+            log_error(&format!("{}\n", e));
+            exit(1);
+        },
+    }
+
+    return;
 }
 
 /// ### private function to generate equivalent expressions
 /// ### with expressions from an input file
+/// ### expressions are generated by a pool of `jobs` workers,
+/// ### and written into output file in input order, a worker does not
+/// ### start an expression more than `jobs * PENDING_PER_JOB` expressions
+/// ### ahead of the next one to write, so the reorder buffer is bounded,
+/// ### finished expressions are recorded in `<output filepath>.ckpt`,
+/// ### failed expressions are written as `"failed"` records (jsonl)
+/// ### or into `<output filepath>.err` (txt), and the batch continues
 /// #### Argument
-/// * `cli` - pre-processed command line arguments
+/// * `config` - generation config
 /// #### Return
 /// * `None`
-fn generate_file(cli: &mut Vec<CliDtype>) {
+fn generate_file(config: &GenerationConfig) {
+    let input_filepath = config.input_filepath.as_ref().unwrap();
+    let output_filepath = config.output_filepath.as_ref().unwrap();
+
     /* Open the input file and create output file */
-    let input_file = match File::options().read(true).write(false).open(&cli[5].to_string()) {
+    let input_file = match File::options().read(true).write(false).open(input_filepath) {
         Ok(input_file) => { input_file },
         Err(e) => {
-            log_error(&format!("Failed to open input file '{}'.\n", &cli[5].to_string()));
+            log_error(&format!("Failed to open input file '{}'.\n", input_filepath));
             log_error(&format!("{}\n", e));
             exit(1);
         },
     };
-    let output_file = match File::create(&cli[6].to_string()) {
+    let resume = config.resume;
+    let output_file = match File::options().write(true).create(true).truncate(!resume).open(output_filepath) {
         Ok(output_file) => { output_file },
         Err(e) => {
-            log_error(&format!("Failed to create output file '{}'.\n", &cli[6].to_string()));
+            log_error(&format!("Failed to create output file '{}'.\n", output_filepath));
+            log_error(&format!("{}\n", e));
+            exit(1);
+        },
+    };
+
+    write_config(config, output_filepath);
+
+    /* Open the checkpoint manifest, load finished expressions when resuming */
+    let ckpt_filepath = format!("{}.ckpt", output_filepath);
+    let checkpoint = match resume {
+        true => { Checkpoint::load(&ckpt_filepath) },
+        false => { Checkpoint::create(&ckpt_filepath) },
+    };
+    let mut checkpoint = match checkpoint {
+        Ok(checkpoint) => { checkpoint },
+        Err(e) => {
+            log_error(&format!("Failed to open checkpoint manifest '{}'.\n", ckpt_filepath));
             log_error(&format!("{}\n", e));
             exit(1);
         },
     };
+    /* drop the partial blocks and records written after the last finished expression */
+    let offsets = checkpoint.get_offsets();
+    if resume { truncate_to_offset(&output_file, output_filepath, offsets.output); }
+
+    /* Open the statistics file, continue from the checkpoint when resuming */
+    let stats_file = match &config.stats_filepath {
+        Some(stats_fpath) => {
+            match File::options().write(true).create(true).truncate(!resume).open(stats_fpath) {
+                Ok(stats_file) => {
+                    if resume { truncate_to_offset(&stats_file, stats_fpath, offsets.stats); }
+                    Some(stats_file)
+                },
+                Err(e) => {
+                    log_error(&format!("Failed to create statistics file '{}'.\n", stats_fpath));
+                    log_error(&format!("{}\n", e));
+                    exit(1);
+                },
+            }
+        },
+        None => { None },
+    };
+    let mut stats_writer = stats_file.as_ref().map(BufWriter::new);
 
     /* Create buffered reader and writer for the input and output files */
     let reader = BufReader::new(&input_file);
     let mut writer = BufWriter::new(&output_file);
 
-    cli.pop();
-
+    /* read all input expressions */
+    let mut input_exprs: Vec<String> = vec![];
     for input_expr in reader.lines() {
-        /* read 1 expression and write into output file */
-        let input_expr = match input_expr {
-            Ok(input_expr) => { input_expr },
+        match input_expr {
+            Ok(input_expr) => { input_exprs.push(input_expr); },
             Err(e) => {
                 log_error(&format!("Failed to read line from input file '{:?}'.\n", input_file));
                 log_error(&format!("{}\n", e));
                 exit(1);
             },
         };
-        match writeln!(writer, "{}", &input_expr.replace(|c| c == '(' || c == ')', "")) {
-            Ok(_) => {},
-            Err(e) => {
-                log_error(&format!("Failed to write input expr '{}' into output file '{:?}'.\n", input_expr, output_file));
-                log_error(&format!("{}\n", e));
-                exit(1);
-            },
-        };
+    }
 
-        /* start extraction and get equivalent expressions */
-        cli[5] = CliDtype::String(input_expr);
-        let start_time = Instant::now();
-        let equiv_exprs = generate_exprs(cli.clone());
-        let end_time = Instant::now();
-        let elapsed_time = end_time.duration_since(start_time).as_secs();
-        log_info(&format!("Total run time: {}s\n\n", elapsed_time));
+    let jobs = config.jobs as usize;
+    if config.deterministic { intern_symbols(&input_exprs); }
+
+    /* indices of unfinished input expressions */
+    let todo: Vec<usize> = (0..input_exprs.len()).filter(|idx| !checkpoint.is_done(idx + 1)).collect();
+    if resume {
+        log_info(&format!("Resume from checkpoint, skip {} finished expression(s).\n", input_exprs.len() - todo.len()));
+    }
 
-        /* write equivalent expressions into output file */
-        for expr in &equiv_exprs {
-            match writeln!(writer, "{}", expr) {
-                Ok(_) => {},
+    let jsonl = config.format == OutputFormat::Jsonl;
+
+    /* Open the error log of failed expressions (txt), continue from the checkpoint when resuming */
+    let err_filepath = format!("{}.err", output_filepath);
+    let err_file = match jsonl {
+        true => { None },
+        false => {
+            match File::options().write(true).create(true).truncate(!resume).open(&err_filepath) {
+                Ok(err_file) => {
+                    if resume { truncate_to_offset(&err_file, &err_filepath, offsets.err); }
+                    Some(err_file)
+                },
                 Err(e) => {
-                    log_error(&format!("Failed to write expr '{}' into output file '{:?}'.\n", expr, output_file));
+                    log_error(&format!("Failed to create error log '{}'.\n", err_filepath));
                     log_error(&format!("{}\n", e));
                     exit(1);
                 },
-            };
+            }
+        },
+    };
+    let mut err_writer = err_file.as_ref().map(BufWriter::new);
+    let mut n_failed: usize = 0;
+
+    let next_idx = AtomicUsize::new(0);
+    /* # of written expressions, workers wait on it to stay within the window */
+    let n_written = (Mutex::new(0usize), Condvar::new());
+    let window = jobs * PENDING_PER_JOB;
+    let (sender, receiver) = mpsc::sync_channel::<(usize, Result<(HashSet<String>, ExprStats), GenError>)>(jobs);
+
+    thread::scope(|scope| {
+        /* start workers, each worker owns its generator */
+        for _ in 0..jobs.min(todo.len()) {
+            let sender = sender.clone();
+            let input_exprs = &input_exprs;
+            let todo = &todo;
+            let next_idx = &next_idx;
+            let n_written = &n_written;
+            scope.spawn(move || {
+                let mut generator = Generator::from_config(config);
+                loop {
+                    let idx = next_idx.fetch_add(1, Ordering::Relaxed);
+                    if idx >= todo.len() { break; }
+                    {
+                        let (lock, cvar) = n_written;
+                        let mut written = lock.lock().unwrap();
+                        while idx >= *written + window { written = cvar.wait(written).unwrap(); }
+                    }
+
+                    /* start extraction and get equivalent expressions, isolate panics */
+                    let input_expr = &input_exprs[todo[idx]];
+                    generator = generator.with_budget(config.budget());
+                    let start_time = Instant::now();
+                    let result = match panic::catch_unwind(AssertUnwindSafe(|| generator.generate_exprs(input_expr))) {
+                        Ok(result) => { result.map(|equiv_exprs| (equiv_exprs, generator.get_stats().clone())) },
+                        Err(payload) => {
+                            /* the generator may be left in an inconsistent state */
+                            generator = Generator::from_config(config);
+                            Err(GenError::Panic { expr: input_expr.clone(), reason: panic_message(&payload) })
+                        },
+                    };
+                    let end_time = Instant::now();
+                    let elapsed_time = end_time.duration_since(start_time).as_secs();
+                    log_info(&format!("Total run time: {}s\n\n", elapsed_time));
+
+                    if sender.send((idx, result)).is_err() { break; }
+                }
+            });
         }
-        match writeln!(writer, "") {
-            Ok(_) => {},
-            Err(e) => {
-                log_error(&format!("Failed to write '' into output file '{:?}'.\n", output_file));
-                log_error(&format!("{}\n", e));
-                exit(1);
-            },
-        };
+        drop(sender);
 
-        /* flush the output stream */
-        match writer.flush() {
-            Ok(_) => {},
-            Err(e) => {
-                log_error(&format!("Failed to flush buffer to output file '{:?}'.\n", output_file));
-                log_error(&format!("{}\n", e));
-                exit(1);
-            },
+        /* write equivalent expressions into output file in input order */
+        let mut pending: BTreeMap<usize, Result<(HashSet<String>, ExprStats), GenError>> = BTreeMap::new();
+        let mut next_write: usize = 0;
+        for (idx, result) in receiver {
+            pending.insert(idx, result);
+            while let Some(result) = pending.remove(&next_write) {
+                let line_idx = todo[next_write];
+                match result {
+                    Ok((equiv_exprs, stats)) => {
+                        if config.deterministic && !stats.is_reproducible() {
+                            log_warn(&format!("Line {}: a time limit fired, the output is not reproducible.\n", line_idx + 1));
+                        }
+                        if jsonl {
+                            write_jsonl(&mut writer, &output_file, &input_exprs[line_idx], &equiv_exprs, &stats, config.deterministic);
+                        } else {
+                            write_exprs(&mut writer, &output_file, &input_exprs[line_idx], &equiv_exprs);
+                        }
+                        if let (Some(stats_writer), Some(stats_file)) = (stats_writer.as_mut(), stats_file.as_ref()) {
+                            write_stats(stats_writer, stats_file, line_idx + 1, &input_exprs[line_idx], &stats);
+                        }
+                    },
+                    Err(error) => {
+                        log_error(&format!("Line {}: {}\n", line_idx + 1, error));
+                        n_failed += 1;
+                        match (err_writer.as_mut(), err_file.as_ref()) {
+                            (Some(err_writer), Some(err_file)) => {
+                                write_failure(err_writer, err_file, false, line_idx + 1, &input_exprs[line_idx], &error);
+                            },
+                            _ => {
+                                write_failure(&mut writer, &output_file, true, line_idx + 1, &input_exprs[line_idx], &error);
+                            },
+                        }
+                    },
+                }
+
+                /* record the finished expression with the file offsets after its records,
+                   once the records are on disk */
+                let offsets = synced_offset(&mut writer, &output_file).and_then(|output| {
+                    let stats = match (stats_writer.as_mut(), stats_file.as_ref()) {
+                        (Some(stats_writer), Some(stats_file)) => { synced_offset(stats_writer, stats_file)? },
+                        _ => { 0 },
+                    };
+                    let err = match (err_writer.as_mut(), err_file.as_ref()) {
+                        (Some(err_writer), Some(err_file)) => { synced_offset(err_writer, err_file)? },
+                        _ => { 0 },
+                    };
+                    return Ok(CheckpointOffsets { output, stats, err });
+                });
+                match offsets.and_then(|offsets| checkpoint.record(line_idx + 1, offsets)) {
+                    Ok(_) => {},
+                    Err(e) => {
+                        log_error(&format!("Failed to record line {} into checkpoint manifest '{}'.\n", line_idx + 1, ckpt_filepath));
+                        log_error(&format!("{}\n", e));
+                        exit(1);
+                    },
+                }
+                next_write += 1;
+                let (lock, cvar) = &n_written;
+                *lock.lock().unwrap() = next_write;
+                cvar.notify_all();
+            }
         }
+    });
+    if n_failed > 0 {
+        log_warn(&format!("{} expression(s) failed.\n", n_failed));
     }
 
     /* flush the output stream */
@@ -205,19 +549,237 @@ fn generate_file(cli: &mut Vec<CliDtype>) {
 /// #### Return
 /// * `None`
 pub fn generate() {
-    let mut cli = parse_args();
+    let config = parse_args();
+    log_info(&format!("Generation config: {}\n", config.to_json()));
 
-    if cli.len() == 6 {
+    if let Some(input_expr) = &config.input_expr {
+        let mut generator = Generator::from_config(&config).with_budget(config.budget());
         let start_time = Instant::now();
-        let equiv_exprs = generate_exprs(cli.clone());
-        for expr in &equiv_exprs {
+        let equiv_exprs = match generator.generate_exprs(input_expr) {
+            Ok(equiv_exprs) => { equiv_exprs },
+            Err(e) => {
+                log_error(&format!("{}\n", e));
+                exit(1);
+            },
+        };
+        for expr in sort_exprs(&equiv_exprs) {
             log_info(&format!("{}\n", expr));
         }
         let end_time = Instant::now();
         let elapsed_time = end_time.duration_since(start_time).as_secs();
         log_info(&format!("Total run time: {}s\n", elapsed_time));
     }
-    else { generate_file(&mut cli); }
+    else { generate_file(&config); }
 
     return;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ### function to write an input file into a fresh temporary directory
+    /// ### and build a fast, reproducible batch config around it
+    fn batch_config(name: &str, input_exprs: &[&str]) -> GenerationConfig {
+        let dir = std::env::temp_dir().join(format!("egen_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let input_filepath = dir.join("input.txt");
+        std::fs::write(&input_filepath, input_exprs.join("\n") + "\n").unwrap();
+
+        return GenerationConfig {
+            extraction: ExtractionKind::BottomUp,
+            deterministic: true,
+            n_equiv_exprs: 5,
+            init_token_limit: 4,
+            max_token_limit: 4,
+            node_limit: 1_000,
+            input_filepath: Some(input_filepath.to_str().unwrap().to_string()),
+            output_filepath: Some(dir.join("output.txt").to_str().unwrap().to_string()),
+            stats_filepath: Some(dir.join("stats.jsonl").to_str().unwrap().to_string()),
+            ..Default::default()
+        };
+    }
+
+    #[test]
+    fn parallel_output_order() {
+        let input_exprs = ["(sin x)", "(cos x)", "(tan x)", "(* x x)", "(+ x 1)", "(sin y)", "(cos y)"];
+        let mut outputs = vec![];
+        for jobs in [1, 3] {
+            let config = GenerationConfig { jobs, ..batch_config(&format!("jobs{}", jobs), &input_exprs) };
+            generate_file(&config);
+            let output_filepath = config.output_filepath.unwrap();
+            outputs.push(std::fs::read(&output_filepath).unwrap());
+            std::fs::remove_dir_all(std::path::Path::new(&output_filepath).parent().unwrap()).unwrap();
+        }
+
+        assert!(!outputs[0].is_empty());
+        assert_eq!(outputs[0], outputs[1]);
+    }
+
+    #[test]
+    fn jsonl_round_trip() {
+        let mut generator = Generator::new(false, 5, 4, 4, 60).with_extraction(ExtractionKind::BottomUp);
+        let equiv_exprs = generator.generate_exprs("(sin x)").unwrap();
+        let stats = generator.get_stats().clone();
+
+        let filepath = std::env::temp_dir().join(format!("egen_jsonl_{}.jsonl", std::process::id()));
+        let file = File::create(&filepath).unwrap();
+        let mut writer = BufWriter::new(&file);
+        write_jsonl(&mut writer, &file, "(sin x)", &equiv_exprs, &stats, false);
+        write_jsonl(&mut writer, &file, "(cos x)", &HashSet::default(), &ExprStats::default(), true);
+        drop(writer);
+
+        let records: Vec<serde_json::Value> = std::fs::read_to_string(&filepath)
+            .unwrap()
+            .lines()
+            .map(|record| serde_json::from_str(record).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["input"], "(sin x)");
+        let read_exprs: Vec<String> = serde_json::from_value(records[0]["equiv_exprs"].clone()).unwrap();
+        assert_eq!(read_exprs.iter().collect::<Vec<_>>(), sort_exprs(&equiv_exprs));
+        assert_eq!(records[0]["stats"], stats.to_json());
+
+        /* an expression without equivalent expressions is still 1 record */
+        assert_eq!(records[1]["input"], "(cos x)");
+        assert_eq!(records[1]["equiv_exprs"], serde_json::json!([]));
+        assert_eq!(records[1]["stats"], ExprStats::default().to_untimed_json());
+
+        std::fs::remove_file(&filepath).unwrap();
+    }
+
+    #[test]
+    fn stats_records() {
+        let input_exprs = ["(sin x)", "(* x x)"];
+        let config = batch_config("stats", &input_exprs);
+        let output_filepath = config.output_filepath.clone().unwrap();
+        generate_file(&config);
+
+        let records: Vec<serde_json::Value> = std::fs::read_to_string(config.stats_filepath.as_ref().unwrap())
+            .unwrap()
+            .lines()
+            .map(|record| serde_json::from_str(record).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        for (idx, record) in records.iter().enumerate() {
+            let mut keys: Vec<&str> = record.as_object().unwrap().keys().map(String::as_str).collect();
+            keys.sort();
+            assert_eq!(keys, vec![
+                "grammar_time", "input", "line", "n_candidates", "n_eclasses", "n_enodes", "n_iterations", "rounds",
+                "saturation", "saturation_time", "stop_reason", "term_counts", "time", "time_limit_reached", "token_limit",
+            ]);
+            assert_eq!(record["line"], idx + 1);
+            assert_eq!(record["input"], input_exprs[idx]);
+            assert!(record["n_eclasses"].as_u64().unwrap() > 0);
+            assert!(record["n_enodes"].as_u64().unwrap() >= record["n_eclasses"].as_u64().unwrap());
+            assert_eq!(record["term_counts"].as_array().unwrap().len(), 5);
+            let rounds = record["rounds"].as_array().unwrap();
+            assert!(!rounds.is_empty());
+            assert!(rounds.iter().all(|round| round["token_limit"] == 4 && round.get("extraction_time").is_some()));
+        }
+        std::fs::remove_dir_all(std::path::Path::new(&output_filepath).parent().unwrap()).unwrap();
+
+        /* the untimed statistics of the jsonl output are the same on every run */
+        let mut outputs = vec![];
+        for run in 0..2 {
+            let config = GenerationConfig {
+                format: OutputFormat::Jsonl,
+                stats_filepath: None,
+                ..batch_config(&format!("untimed{}", run), &input_exprs)
+            };
+            generate_file(&config);
+            let output_filepath = config.output_filepath.unwrap();
+            outputs.push(std::fs::read_to_string(&output_filepath).unwrap());
+            std::fs::remove_dir_all(std::path::Path::new(&output_filepath).parent().unwrap()).unwrap();
+        }
+        assert_eq!(outputs[0], outputs[1]);
+        for record in outputs[0].lines() {
+            let stats = &serde_json::from_str::<serde_json::Value>(record).unwrap()["stats"];
+            assert!(["time", "saturation_time", "grammar_time"].iter().all(|key| stats.get(key).is_none()));
+            assert!(stats["rounds"].as_array().unwrap().iter().all(|round| round.get("extraction_time").is_none()));
+        }
+    }
+
+    #[test]
+    fn failed_line_is_logged() {
+        /* the default node limit lets the saturation of line 2 reach its analysis panic */
+        let config = GenerationConfig {
+            node_limit: GenerationConfig::default().node_limit,
+            ..batch_config("failed", &["(sin x)", "(pow -1 0.5)", "(cos x)"])
+        };
+        let output_filepath = config.output_filepath.clone().unwrap();
+        generate_file(&config);
+        let read = |filepath: &str| { return std::fs::read_to_string(filepath).unwrap(); };
+
+        /* the analysis panic of line 2 is logged and line 3 is still generated */
+        let err: Vec<String> = read(&format!("{}.err", output_filepath)).lines().map(String::from).collect();
+        assert_eq!(err.len(), 1);
+        assert!(err[0].starts_with("2\t(pow -1 0.5)\t"));
+        let output = read(&output_filepath);
+        assert!(output.lines().any(|line| line == "sin x"));
+        assert!(output.lines().any(|line| line == "cos x"));
+        assert!(!output.lines().any(|line| line == "pow -1 0.5"));
+        let lines: Vec<u64> = read(config.stats_filepath.as_ref().unwrap())
+            .lines()
+            .map(|record| serde_json::from_str::<serde_json::Value>(record).unwrap()["line"].as_u64().unwrap())
+            .collect();
+        assert_eq!(lines, vec![1, 3]);
+        std::fs::remove_dir_all(std::path::Path::new(&output_filepath).parent().unwrap()).unwrap();
+
+        /* a failure of the jsonl output maps back to its input line, also for repeated inputs */
+        let config = GenerationConfig {
+            node_limit: GenerationConfig::default().node_limit,
+            format: OutputFormat::Jsonl,
+            ..batch_config("failed_jsonl", &["(pow -1 0.5)", "(sin x)", "(pow -1 0.5)"])
+        };
+        let output_filepath = config.output_filepath.clone().unwrap();
+        generate_file(&config);
+        let records: Vec<serde_json::Value> = read(&output_filepath)
+            .lines()
+            .map(|record| serde_json::from_str(record).unwrap())
+            .collect();
+        assert_eq!(records.len(), 3);
+        for (idx, record) in records.iter().enumerate() {
+            assert_eq!(record.get("failed").is_some(), idx != 1);
+        }
+        assert_eq!(records[0]["line"], 1);
+        assert_eq!(records[2]["line"], 3);
+        assert_eq!(records[2]["input"], "(pow -1 0.5)");
+        assert!(records[2]["error"].as_str().unwrap().contains("Merged non-equal constants"));
+
+        std::fs::remove_dir_all(std::path::Path::new(&output_filepath).parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn resume_truncates_every_file() {
+        let config = batch_config("resume", &["(sin x)", "(cos x", "(cos x)", "(tan x)"]);
+        let output_filepath = config.output_filepath.clone().unwrap();
+        let stats_filepath = config.stats_filepath.clone().unwrap();
+        let err_filepath = format!("{}.err", output_filepath);
+        let ckpt_filepath = format!("{}.ckpt", output_filepath);
+        generate_file(&config);
+        let read = |filepath: &str| { return std::fs::read_to_string(filepath).unwrap(); };
+        let (output, stats, err) = (read(&output_filepath), read(&stats_filepath), read(&err_filepath));
+        assert_eq!(stats.lines().count(), 3);
+        assert_eq!(err.lines().count(), 1);
+
+        /* crash after line 1: lines 2-4 are written but not recorded, plus a partial record */
+        let ckpt = read(&ckpt_filepath);
+        std::fs::write(&ckpt_filepath, ckpt.lines().next().unwrap().to_string() + "\n").unwrap();
+        for filepath in [&output_filepath, &stats_filepath, &err_filepath] {
+            File::options().append(true).open(filepath).unwrap().write_all(b"partial").unwrap();
+        }
+        generate_file(&GenerationConfig { resume: true, ..config });
+
+        assert_eq!(read(&output_filepath), output);
+        assert_eq!(read(&err_filepath), err);
+        let lines: Vec<u64> = read(&stats_filepath)
+            .lines()
+            .map(|record| serde_json::from_str::<serde_json::Value>(record).unwrap()["line"].as_u64().unwrap())
+            .collect();
+        assert_eq!(lines, vec![1, 3, 4]);
+
+        std::fs::remove_dir_all(std::path::Path::new(&output_filepath).parent().unwrap()).unwrap();
+    }
+}
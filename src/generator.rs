@@ -0,0 +1,939 @@
+use crate::*;
+use num_bigint::BigUint;
+use quanta::Instant;
+
+/// Generator Struct
+/// store hyperparameters, grammar, intermediate extraction
+/// states and equivalent expressions of a generation,
+/// so that multiple generations can run in one process
+pub struct Generator {
+    /// optimized extraction flag
+    optimized: bool,
+    /// extraction strategy
+    extraction: ExtractionKind,
+    /// random seed of the production order and sample extraction
+    seed: u64,
+    /// cost function of top-k extraction
+    cost: CostKind,
+    /// number of equivalent expressions
+    n_equiv_exprs: usize,
+    /// selection of the returned equivalent expressions
+    selection: SelectionKind,
+    /// candidate pool size as a multiple of the number of equivalent expressions
+    pool_factor: usize,
+    /// oversampling factor of early termination, None to extract until the time limit
+    oversample: Option<f64>,
+    /// initial token limit
+    init_token_limit: u16,
+    /// maximum token limit
+    max_token_limit: u16,
+    /// initial time limit in sec
+    init_time_limit: u32,
+    /// strategy to escalate token/time limits between extraction rounds
+    escalation: Box<dyn Escalation>,
+    /// skip expressions that can never reach the number of equivalent expressions
+    skip_unreachable: bool,
+    /// raise the initial token limit to the smallest limit with enough terms
+    raise_token_limit: bool,
+    /// extract derivative-free expressions only (without `d` and `i`)
+    derivative_free: bool,
+    /// equivalences of the dedupe of equivalent expressions
+    canonicalization: Canonicalization,
+    /// Runner limits and rewrite scheduler of equality saturation
+    saturation: SaturationConfig,
+    /// budget of the generation(s), covering saturation and every extraction round
+    budget: Budget,
+    /// token limit of the current extraction
+    pub(crate) token_limit: u16,
+    /// time limit of the current extraction in sec
+    pub(crate) time_limit: u32,
+    /// budget of the current extraction, the time limit capped by the generation budget
+    pub(crate) round_budget: Budget,
+    /// filters of the productions, sentential forms and expressions
+    pub(crate) filters: Vec<Box<dyn ExprFilter>>,
+    /// grammar generated from MathEGraph
+    pub(crate) grammar: Grammar,
+    /// intermediate extraction states
+    pub(crate) state: HashSet<Vec<GrammarToken>>,
+    /// equivalent expression results
+    pub(crate) equiv_exprs: HashSet<String>,
+    /// whether the time limit fired during the current extraction
+    pub(crate) timed_out: bool,
+    /// canonical keys of the current extraction (see Canonicalization)
+    pub(crate) canonical_keys: HashSet<String>,
+    /// whether enough distinct expressions are found during the current extraction
+    pub(crate) target_reached: bool,
+    /// statistics of the last generation
+    stats: ExprStats,
+}
+
+impl Generator {
+    /// ### default constructor
+    /// #### Arguments
+    /// * `optimized` - optimized extraction flag
+    /// * `n_equiv_exprs` - number of equivalent expressions
+    /// * `init_token_limit` - initial token limit
+    /// * `max_token_limit` - maximum token limit
+    /// * `init_time_limit` - initial time limit in sec
+    /// #### Return
+    /// * `Generator`
+    pub fn new(optimized: bool, n_equiv_exprs: usize, init_token_limit: u16, max_token_limit: u16, init_time_limit: u32) -> Self {
+        Generator {
+            optimized,
+            extraction: ExtractionKind::Dfs,
+            seed: 0,
+            cost: CostKind::Size,
+            n_equiv_exprs,
+            selection: SelectionKind::All,
+            pool_factor: POOL_FACTOR,
+            oversample: None,
+            init_token_limit,
+            max_token_limit,
+            init_time_limit,
+            escalation: Box::new(FixedEscalation::default()),
+            skip_unreachable: false,
+            raise_token_limit: false,
+            derivative_free: false,
+            canonicalization: Default::default(),
+            saturation: Default::default(),
+            budget: Default::default(),
+            token_limit: init_token_limit,
+            time_limit: init_time_limit,
+            round_budget: Default::default(),
+            filters: default_filters(),
+            grammar: Default::default(),
+            state: Default::default(),
+            equiv_exprs: Default::default(),
+            timed_out: false,
+            canonical_keys: Default::default(),
+            target_reached: false,
+            stats: Default::default(),
+        }
+    }
+
+    /// ### member function to change the extraction strategy,
+    /// ### default is ExtractionKind::Dfs
+    /// #### Arguments
+    /// * `self`
+    /// * `extraction` - extraction strategy
+    /// #### Return
+    /// * `Generator`
+    pub fn with_extraction(mut self, extraction: ExtractionKind) -> Self {
+        self.extraction = extraction;
+        return self;
+    }
+
+    /// ### member function to change the random seed of the production
+    /// ### order and sample extraction, default is 0 (canonical order)
+    /// #### Arguments
+    /// * `self`
+    /// * `seed` - random seed
+    /// #### Return
+    /// * `Generator`
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        return self;
+    }
+
+    /// ### member function to change the cost function
+    /// ### of top-k extraction, default is CostKind::Size
+    /// #### Arguments
+    /// * `self`
+    /// * `cost` - cost function
+    /// #### Return
+    /// * `Generator`
+    pub fn with_cost(mut self, cost: CostKind) -> Self {
+        self.cost = cost;
+        return self;
+    }
+
+    /// ### member function to change the selection of the returned
+    /// ### equivalent expressions, default is SelectionKind::All,
+    /// ### diverse selections pick them from a candidate pool of
+    /// ### `pool_factor` * `n_equiv_exprs` expressions
+    /// #### Arguments
+    /// * `self`
+    /// * `selection` - selection of the returned equivalent expressions
+    /// * `pool_factor` - candidate pool factor
+    /// #### Return
+    /// * `Generator`
+    pub fn with_selection(mut self, selection: SelectionKind, pool_factor: usize) -> Self {
+        self.selection = selection;
+        self.pool_factor = pool_factor;
+        return self;
+    }
+
+    /// ### member function to change early termination of extraction,
+    /// ### extraction stops once `oversample` * `n_equiv_exprs` expressions
+    /// ### distinct after post-processing are found, None extracts until
+    /// ### the time limit, default is None
+    /// #### Arguments
+    /// * `self`
+    /// * `oversample` - oversampling factor, ≥ 1
+    /// #### Return
+    /// * `Generator`
+    pub fn with_early_stop(mut self, oversample: Option<f64>) -> Self {
+        self.oversample = oversample;
+        return self;
+    }
+
+    /// ### member function to change the escalation strategy
+    /// ### of token/time limits, default is FixedEscalation
+    /// #### Arguments
+    /// * `self`
+    /// * `escalation` - escalation strategy
+    /// #### Return
+    /// * `Generator`
+    pub fn with_escalation(mut self, escalation: impl Escalation + 'static) -> Self {
+        self.escalation = Box::new(escalation);
+        return self;
+    }
+
+    /// ### member function to change whether expressions with fewer terms
+    /// ### than the number of equivalent expressions within the maximum
+    /// ### token limit are skipped, default is false
+    /// #### Arguments
+    /// * `self`
+    /// * `skip_unreachable` - skip unreachable expressions flag
+    /// #### Return
+    /// * `Generator`
+    pub fn with_skip_unreachable(mut self, skip_unreachable: bool) -> Self {
+        self.skip_unreachable = skip_unreachable;
+        return self;
+    }
+
+    /// ### member function to change whether the initial token limit is raised
+    /// ### to the smallest limit with at least as many terms as candidates,
+    /// ### since lower limits can never reach them, default is false
+    /// ### (the smallest limit is only logged)
+    /// #### Arguments
+    /// * `self`
+    /// * `raise_token_limit` - raise initial token limit flag
+    /// #### Return
+    /// * `Generator`
+    pub fn with_raise_token_limit(mut self, raise_token_limit: bool) -> Self {
+        self.raise_token_limit = raise_token_limit;
+        return self;
+    }
+
+    /// ### member function to change whether only derivative-free expressions
+    /// ### (without `d` and `i`) are extracted, the derivative eclasses are
+    /// ### resolved through the productions saturation derives for them,
+    /// ### default is false
+    /// #### Arguments
+    /// * `self`
+    /// * `derivative_free` - derivative-free flag
+    /// #### Return
+    /// * `Generator`
+    pub fn with_derivative_free(mut self, derivative_free: bool) -> Self {
+        self.derivative_free = derivative_free;
+        return self;
+    }
+
+    /// ### member function to change the equivalences of the dedupe of
+    /// ### equivalent expressions, default is commutativity and associativity
+    /// ### of + and * only
+    /// #### Arguments
+    /// * `self`
+    /// * `canonicalization` - canonicalization
+    /// #### Return
+    /// * `Generator`
+    pub fn with_canonicalization(mut self, canonicalization: Canonicalization) -> Self {
+        self.canonicalization = canonicalization;
+        return self;
+    }
+
+    /// ### member function to get the equivalences of the dedupe
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `&Canonicalization` - canonicalization
+    pub fn get_canonicalization(&self) -> &Canonicalization { return &self.canonicalization; }
+
+    /// ### member function to change the Runner limits and rewrite scheduler
+    /// ### of equality saturation
+    /// #### Arguments
+    /// * `self`
+    /// * `saturation` - saturation config
+    /// #### Return
+    /// * `Generator`
+    pub fn with_saturation(mut self, saturation: SaturationConfig) -> Self {
+        self.saturation = saturation;
+        return self;
+    }
+
+    /// ### member function to add a filter of the productions,
+    /// ### sentential forms and expressions
+    /// #### Arguments
+    /// * `self`
+    /// * `filter` - filter
+    /// #### Return
+    /// * `Generator`
+    pub fn with_filter(mut self, filter: impl ExprFilter + 'static) -> Self {
+        self.filters.push(Box::new(filter));
+        return self;
+    }
+
+    /// ### member function to replace the filters of the productions,
+    /// ### sentential forms and expressions, default is default_filters()
+    /// #### Arguments
+    /// * `self`
+    /// * `filters` - filters
+    /// #### Return
+    /// * `Generator`
+    pub fn with_filters(mut self, filters: Vec<Box<dyn ExprFilter>>) -> Self {
+        self.filters = filters;
+        return self;
+    }
+
+    /// ### member function to change the budget of the generation(s),
+    /// ### a clone of the budget can cancel them from another thread,
+    /// ### default is no time limit
+    /// #### Arguments
+    /// * `self`
+    /// * `budget` - budget of the generation(s)
+    /// #### Return
+    /// * `Generator`
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = budget;
+        return self;
+    }
+
+    /// ### constructor from a generation config
+    /// #### Argument
+    /// * `config` - generation config
+    /// #### Return
+    /// * `Generator`
+    pub fn from_config(config: &GenerationConfig) -> Self {
+        return Generator::new(config.optimized, config.n_equiv_exprs, config.init_token_limit,
+                              config.max_token_limit, config.init_time_limit)
+            .with_extraction(config.extraction)
+            .with_seed(config.seed)
+            .with_cost(config.cost)
+            .with_skip_unreachable(config.skip_unreachable)
+            .with_raise_token_limit(config.raise_token_limit)
+            .with_derivative_free(config.derivative_free)
+            .with_canonicalization(config.canonicalization())
+            .with_selection(config.selection, config.pool_factor)
+            .with_early_stop(if config.early_stop { Some(config.oversample) } else { None })
+            .with_filters(config.filters())
+            .with_saturation(config.saturation())
+            .with_escalation(config.escalation());
+    }
+
+    /// ### member function to generate equivalent expressions
+    /// ### with 1 input expression
+    /// #### Arguments
+    /// * `self`
+    /// * `input_expr` - input expression
+    /// #### Return
+    /// * `Result` - HashSet<String> of equivalent expressions, or parse/analysis error
+    pub fn generate_exprs(&mut self, input_expr: &str) -> Result<HashSet<String>, GenError> {
+        let (equiv_exprs, _) = self.generate_exprs_shared(&[input_expr.to_string()])?.pop().unwrap();
+
+        return Ok(equiv_exprs);
+    }
+
+    /// ### member function to generate equivalent expressions
+    /// ### with multiple input expressions sharing 1 egraph,
+    /// ### the egraph is saturated once and every input expression
+    /// ### is extracted from its own root eclass
+    /// #### Arguments
+    /// * `self`
+    /// * `input_exprs` - input expressions
+    /// #### Return
+    /// * `Result` - equivalent expressions and statistics of every input expression
+    ///   (none without input expressions), or parse/analysis error
+    pub fn generate_exprs_shared(&mut self, input_exprs: &[String]) -> Result<Vec<(HashSet<String>, ExprStats)>, GenError> {
+        if input_exprs.is_empty() { return Ok(vec![]); }
+
+        let setup_start_time = Instant::now();
+        let (init_exprs, setup_stats) = self.setup(input_exprs)?;
+        let setup_time = setup_start_time.elapsed().as_secs_f64();
+
+        let mut results = vec![];
+        for (input_expr, init_exprs) in input_exprs.iter().zip(&init_exprs) {
+            if input_exprs.len() > 1 {
+                log_info(&format!("Root expression: {}\n", input_expr));
+            }
+            log_info(&format!("Total # of init expr(s): {}\n", init_exprs.len()));
+            self.stats = setup_stats.clone();
+            let equiv_exprs = self.escalate(init_exprs, setup_time);
+            let equiv_exprs = self.select(equiv_exprs);
+            results.push((equiv_exprs, self.stats.clone()));
+        }
+
+        return Ok(results);
+    }
+
+    /// ### member function to iterate over the equivalent expressions
+    /// ### of 1 input expression lazily, shortest first up to the maximum
+    /// ### token limit, without token/time limit escalation or post-processing
+    /// #### Arguments
+    /// * `self`
+    /// * `input_expr` - input expression
+    /// #### Return
+    /// * `Result` - iterator over tokens of equivalent expressions, or parse/analysis error
+    pub fn iter_equiv_exprs(&mut self, input_expr: &str) -> Result<EquivExprIter<'_>, GenError> {
+        let (init_exprs, setup_stats) = self.setup(&[input_expr.to_string()])?;
+        self.stats = setup_stats;
+
+        return Ok(EquivExprIter::new(&self.grammar, &self.filters, &init_exprs[0], self.max_token_limit as usize)
+            .with_budget(self.budget.clone()));
+    }
+
+    /// ### private member function to saturate 1 egraph with the input expressions
+    /// ### and create its grammar
+    /// #### Arguments
+    /// * `self`
+    /// * `input_exprs` - input expressions
+    /// #### Return
+    /// * `Result` - initial expressions of every input expression and statistics
+    ///   of the setup, or parse/analysis error
+    fn setup(&mut self, input_exprs: &[String]) -> Result<(Vec<Vec<Math>>, ExprStats), GenError> {
+        /* initialize ctx_gr struct and create egraph, grammar, init_rewrite */
+        for input_expr in input_exprs {
+            log_info(&format!("Expression: {}\n", input_expr));
+        }
+        let mut ctx_gr = input_exprs[1..].iter().fold(
+            ContextGrammar::new(input_exprs[0].clone()),
+            |ctx_gr, input_expr| ctx_gr.with_expr(input_expr.clone()),
+        ).with_saturation(self.saturation.clone()).with_seed(self.seed).with_budget(self.budget.clone());
+        ctx_gr.setup()?;
+        pt_egraph_info(&ctx_gr.egraph);
+        let setup_stats = ExprStats {
+            n_eclasses: ctx_gr.egraph.number_of_classes(),
+            n_enodes: ctx_gr.egraph.total_number_of_nodes(),
+            saturation: ctx_gr.stop_reason,
+            n_iterations: ctx_gr.n_iterations,
+            saturation_time: ctx_gr.saturation_time,
+            grammar_time: ctx_gr.grammar_time,
+            ..Default::default()
+        };
+        /* pruned productions are never used by any extraction */
+        let grammar = &ctx_gr.grammar;
+        let keep = |enode: &Math| {
+            return (!self.derivative_free || DerivativeFreeFilter.keep_production(grammar, enode)) &&
+                self.filters.iter().all(|filter| filter.keep_production(grammar, enode));
+        };
+        let init_exprs = ctx_gr.init_exprs
+            .iter()
+            .map(|init_exprs| init_exprs.iter().filter(|init_expr| keep(init_expr)).cloned().collect())
+            .collect();
+        self.grammar = grammar.filtered(keep);
+
+        return Ok((init_exprs, setup_stats));
+    }
+
+    /// ### private member function to extract equivalent expressions
+    /// ### of 1 root eclass, escalating token/time limits between rounds
+    /// ### until the number of equivalent expressions is reached
+    /// #### Arguments
+    /// * `self`
+    /// * `init_exprs` - initial expressions of the root eclass
+    /// * `setup_time` - saturation and grammar creation time in sec
+    /// #### Return
+    /// * `equiv_exprs` - HashSet<String> of equivalent expressions
+    fn escalate(&mut self, init_exprs: &[Math], setup_time: f64) -> HashSet<String> {
+        let gen_start_time = Instant::now();
+        self.token_limit = self.init_token_limit;
+        self.time_limit = self.init_time_limit;
+        self.escalation.reset();
+
+        /* token limits with fewer terms than needed can never succeed */
+        let len_counts = self.count_terms(init_exprs);
+        let mut n_terms = BigUint::default();
+        let mut min_token_limit = None;
+        for (n_tokens, count) in len_counts.iter().enumerate() {
+            n_terms += count;
+            if min_token_limit.is_none() && n_terms >= BigUint::from(self.n_candidates()) {
+                min_token_limit = Some(n_tokens as u16);
+            }
+        }
+        log_info(&format!("Total # of term(s) within max token limit {}: {}\n", self.max_token_limit, n_terms));
+        if self.derivative_free && n_terms == BigUint::default() {
+            log_info("Derivative(s) are unresolved, no derivative-free term within max token limit.\n");
+            self.stats.stop_reason = GenStopReason::Unresolved;
+            self.stats.time = setup_time + gen_start_time.elapsed().as_secs_f64();
+            return Default::default();
+        }
+        if n_terms < BigUint::from(self.n_equiv_exprs) {
+            log_info(&format!("Number of equivalent expressions {} is unreachable.\n", self.n_equiv_exprs));
+            if self.skip_unreachable {
+                self.stats.stop_reason = GenStopReason::Unreachable;
+                self.stats.time = setup_time + gen_start_time.elapsed().as_secs_f64();
+                return Default::default();
+            }
+        }
+        if let Some(stop_reason) = self.budget_stop_reason() {
+            self.stats.stop_reason = stop_reason;
+            self.stats.time = setup_time + gen_start_time.elapsed().as_secs_f64();
+            return Default::default();
+        }
+        let min_token_limit = min_token_limit.unwrap_or(self.max_token_limit);
+        if min_token_limit > self.token_limit {
+            if self.raise_token_limit {
+                log_info(&format!("Raise initial token limit to {}\n", min_token_limit));
+                self.token_limit = min_token_limit;
+            } else {
+                log_info(&format!("Initial token limit {} has too few terms, {} is the smallest limit with enough terms\n",
+                                  self.token_limit, min_token_limit));
+            }
+        }
+
+        loop {
+            let start_time = Instant::now();
+            self.extract(init_exprs);
+            let end_time = Instant::now();
+            let elapsed_time = end_time.duration_since(start_time).as_secs();
+            let extraction_time = end_time.duration_since(start_time).as_secs_f64();
+            log_info(&format!("Expression extraction time: {}s\n", elapsed_time));
+
+            let start_time = Instant::now();
+            let orig_num_exprs = self.equiv_exprs.len();
+            /* post-processing equivalent expressions */
+            let equiv_exprs = self.canonicalization.rm_dupes(&self.equiv_exprs);
+            let end_time = Instant::now();
+            let elapsed_time = end_time.duration_since(start_time).as_secs();
+            let num_exprs = equiv_exprs.len();
+            log_info(&format!("Expression postprocessing time: {}s\n", elapsed_time));
+            log_info(&format!("Total # of expression(s) before postprocessing: {}\n", orig_num_exprs));
+            log_info(&format!("Total # of expression(s) after  postprocessing: {}\n", num_exprs));
+
+            self.stats.rounds.push(RoundStats {
+                token_limit: self.token_limit,
+                time_limit: self.time_limit,
+                extraction_time,
+                n_exprs_before: orig_num_exprs,
+                n_exprs_after: num_exprs,
+                time_limit_reached: self.timed_out,
+                target_reached: self.target_reached,
+            });
+            self.stats.token_limit = self.token_limit;
+            self.stats.time = setup_time + gen_start_time.elapsed().as_secs_f64();
+
+            if num_exprs >= self.n_candidates() {
+                self.stats.stop_reason = GenStopReason::NEquivExprs;
+                return equiv_exprs;
+            }
+            if let Some(stop_reason) = self.budget_stop_reason() {
+                self.stats.stop_reason = stop_reason;
+                return equiv_exprs;
+            }
+
+            log_info("-----------------------------------\n");
+            let limits = match self.escalation.next_limits(&self.stats.rounds, self.stats.time) {
+                Ok(limits) => { limits },
+                Err(stop_reason) => {
+                    self.stats.stop_reason = stop_reason;
+                    return equiv_exprs;
+                },
+            };
+            if limits.token_limit > self.max_token_limit {
+                log_info(&format!("Token limit {} reaches max token limit {}.\n", limits.token_limit, self.max_token_limit));
+                self.stats.stop_reason = GenStopReason::MaxTokenLimit;
+                return equiv_exprs;
+            }
+            self.token_limit = limits.token_limit;
+            log_info(&format!("Increase token limit to {}\n", self.token_limit));
+            self.time_limit = limits.time_limit;
+            log_info(&format!("Increase time limit to {}\n", self.time_limit));
+        }
+    }
+
+    /// ### private member function to check if the budget of the generation
+    /// ### is exhausted
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `Option<GenStopReason>` - why the generation needs to stop, None if it can go on
+    fn budget_stop_reason(&self) -> Option<GenStopReason> {
+        if self.budget.is_cancelled() {
+            log_info("Generation is cancelled.\n");
+            return Some(GenStopReason::Cancelled);
+        }
+        if self.budget.is_exhausted() {
+            log_info("Time budget is spent.\n");
+            return Some(GenStopReason::TimeBudget);
+        }
+
+        return None;
+    }
+
+    /// ### private member function to get the # of candidate expressions
+    /// ### to extract before selection
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `usize` - # of candidate expressions
+    pub(crate) fn n_candidates(&self) -> usize {
+        return match self.selection {
+            SelectionKind::All => { self.n_equiv_exprs },
+            SelectionKind::Edit | SelectionKind::Operator => { self.n_equiv_exprs.saturating_mul(self.pool_factor) },
+        };
+    }
+
+    /// ### member function to get the # of expressions distinct after
+    /// ### post-processing at which extraction stops early
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `Option<usize>` - # of expressions, None if extraction runs until the time limit
+    pub(crate) fn n_target(&self) -> Option<usize> {
+        let oversample = self.oversample?;
+        let n_target = (self.n_candidates() as f64 * oversample).ceil();
+
+        return Some(if n_target >= usize::MAX as f64 { usize::MAX } else { n_target as usize });
+    }
+
+    /// ### private member function to select the returned equivalent
+    /// ### expressions from the candidate expressions
+    /// #### Arguments
+    /// * `self`
+    /// * `equiv_exprs` - candidate expressions
+    /// #### Return
+    /// * `equiv_exprs` - HashSet<String> of selected equivalent expressions
+    fn select(&mut self, equiv_exprs: HashSet<String>) -> HashSet<String> {
+        self.stats.n_candidates = equiv_exprs.len();
+        let distance = match self.selection {
+            SelectionKind::All => { return equiv_exprs; },
+            SelectionKind::Edit => { edit_distance },
+            SelectionKind::Operator => { operator_distance },
+        };
+
+        let start_time = Instant::now();
+        let equiv_exprs = select_diverse(&equiv_exprs, self.n_equiv_exprs, distance);
+        log_info(&format!("Expression selection time: {}s\n", start_time.elapsed().as_secs()));
+        log_info(&format!("Total # of expression(s) after  selection: {}\n", equiv_exprs.len()));
+
+        return equiv_exprs;
+    }
+
+    /// ### private member function to count the terms of the root eclass
+    /// ### by # of tokens up to the maximum token limit over the pruned
+    /// ### grammar (see setup), the counts are recorded in the statistics
+    /// #### Arguments
+    /// * `self`
+    /// * `init_exprs` - initial expressions of the root eclass
+    /// #### Return
+    /// * `Vec<BigUint>` - # of terms, index n is the # of terms of n tokens
+    fn count_terms(&mut self, init_exprs: &[Math]) -> Vec<BigUint> {
+        let term_counts = TermCounts::new(&self.grammar, self.max_token_limit as usize);
+        let len_counts = term_counts.len_counts(init_exprs);
+        for (n_tokens, count) in len_counts.iter().enumerate().skip(1) {
+            log_debug(&format!("# of term(s) of {} token(s): {}\n", n_tokens, count));
+        }
+        self.stats.term_counts = len_counts.iter().map(|count| count.to_string()).collect();
+
+        return len_counts;
+    }
+
+    /// ### member function to get the optimized extraction flag
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `optimized` - optimized extraction flag
+    pub fn get_optimized(&self) -> bool { return self.optimized; }
+
+    /// ### member function to get the extraction strategy
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `extraction` - extraction strategy
+    pub fn get_extraction(&self) -> ExtractionKind { return self.extraction; }
+
+    /// ### member function to get the random seed of the production order
+    /// ### and sample extraction
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `seed` - random seed
+    pub fn get_seed(&self) -> u64 { return self.seed; }
+
+    /// ### member function to get the budget of the generation(s),
+    /// ### clone it to cancel them from another thread
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `budget` - budget of the generation(s)
+    pub fn get_budget(&self) -> &Budget { return &self.budget; }
+
+    /// ### member function to get the cost function of top-k extraction
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `cost` - cost function
+    pub fn get_cost(&self) -> CostKind { return self.cost; }
+
+    /// ### member function to get the number of equivalent expressions
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `n_equiv_exprs` - number of equivalent expressions
+    pub fn get_n_equiv_exprs(&self) -> usize { return self.n_equiv_exprs; }
+
+    /// ### member function to get the grammar of the last generation
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `grammar` - grammar created from egraph
+    pub fn get_grammar(&self) -> &Grammar { return &self.grammar; }
+
+    /// ### member function to get the statistics of the last generation
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `stats` - statistics of the last generation
+    pub fn get_stats(&self) -> &ExprStats { return &self.stats; }
+
+    /// ### member function to get the raw equivalent expressions
+    /// ### of the last extraction (before post-processing)
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `equiv_exprs` - equivalent expressions
+    pub fn get_equiv_exprs(&self) -> &HashSet<String> { return &self.equiv_exprs; }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generators_in_threads() {
+        let handles: Vec<_> = ["(cos x)", "(sin x)"]
+            .into_iter()
+            .map(|input_expr| {
+                std::thread::spawn(move || {
+                    let mut generator = Generator::new(true, 1, 3, 3, 1);
+                    generator.generate_exprs(input_expr).unwrap()
+                })
+            })
+            .collect();
+        let results: Vec<HashSet<String>> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        assert!(results[0].contains("cos x"));
+        assert!(results[1].contains("sin x"));
+    }
+
+    #[test]
+    fn shared_egraph_roots() {
+        let mut generator = Generator::new(true, 1, 3, 3, 1);
+        let results = generator.generate_exprs_shared(&["(cos x)".to_string(), "(sin x)".to_string()]).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert!(results[0].0.contains("cos x") && !results[0].0.contains("sin x"));
+        assert!(results[1].0.contains("sin x") && !results[1].0.contains("cos x"));
+        assert!(generator.generate_exprs_shared(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid_exprs() {
+        let mut generator = Generator::new(true, 1, 3, 3, 1);
+        assert!(matches!(generator.generate_exprs("(cos x"), Err(GenError::Parse { .. })));
+        assert!(matches!(generator.generate_exprs("(foo x y)"), Err(GenError::Parse { .. })));
+
+        /* analysis failures are returned instead of panicking,
+           the rewrites of (pow -1 0.5) merge the constants 1 and 0 */
+        match generator.generate_exprs("(pow -1 0.5)") {
+            Err(GenError::Analysis(message)) => { assert!(message.contains("Merged non-equal constants")); },
+            result => { panic!("expected an analysis error, got {:?}", result); },
+        }
+        assert!(generator.generate_exprs("(cos x)").is_ok());
+    }
+
+    /// ### function to extract the equivalent expressions of an input expression
+    /// ### with 1 extraction strategy, shared by the extraction tests
+    /// #### Arguments
+    /// * `extraction` - extraction strategy
+    /// * `n_equiv_exprs` - number of equivalent expressions
+    /// * `token_limit` - initial and maximum token limit
+    /// * `input_expr` - input expression
+    /// * `setup` - further settings of the generator
+    /// #### Return
+    /// * `HashSet<String>` - extracted expressions
+    fn extract(
+        extraction: ExtractionKind,
+        n_equiv_exprs: usize,
+        token_limit: u16,
+        input_expr: &str,
+        setup: impl FnOnce(Generator) -> Generator,
+    ) -> HashSet<String> {
+        let mut generator = setup(Generator::new(false, n_equiv_exprs, token_limit, token_limit, 60).with_extraction(extraction));
+        generator.generate_exprs(input_expr).unwrap();
+        return generator.get_equiv_exprs().clone();
+    }
+
+    /// ### function to check that no expression left out is shorter than
+    /// ### an extracted one
+    /// #### Arguments
+    /// * `equiv_exprs` - extracted expressions
+    /// * `exhaustive` - all equivalent expressions
+    /// #### Return
+    /// * `None`
+    fn assert_shortest_first(equiv_exprs: &HashSet<String>, exhaustive: &HashSet<String>) {
+        let n_tokens = |expr: &String| { return expr.split(' ').count(); };
+        let longest = equiv_exprs.iter().map(n_tokens).max().unwrap();
+        assert!(exhaustive.difference(equiv_exprs).all(|expr| n_tokens(expr) >= longest));
+    }
+
+    #[test]
+    fn exhaustive_extractions() {
+        let input_expr = "(* (sin x) (cos x))";
+        let exhaustive = extract(ExtractionKind::Dfs, usize::MAX, 5, input_expr, |generator| generator);
+        assert!(!exhaustive.is_empty());
+
+        for extraction in [ExtractionKind::Shortest, ExtractionKind::BottomUp] {
+            assert_eq!(extract(extraction, usize::MAX, 5, input_expr, |generator| generator), exhaustive);
+
+            /* stopped early, only the shortest expressions are extracted */
+            let all_exprs = extract(extraction, usize::MAX, 7, input_expr, |generator| generator);
+            let shortest = extract(extraction, 5, 7, input_expr, |generator| generator.with_early_stop(Some(1.0)));
+            assert!(shortest.len() < all_exprs.len());
+            assert!(shortest.is_subset(&all_exprs));
+            assert_shortest_first(&shortest, &all_exprs);
+        }
+    }
+
+    #[test]
+    fn expr_filters_generation() {
+        let input_expr = "(* (sin x) (cos x))";
+        let exhaustive = extract(ExtractionKind::Shortest, usize::MAX, 6, input_expr, |generator| generator);
+        let filtered = |extraction: ExtractionKind| {
+            return extract(extraction, usize::MAX, 6, input_expr, |generator| {
+                return generator.with_filter(OpFilter { op: "cos".to_string() }).with_filter(DepthFilter { max_depth: 3 });
+            });
+        };
+
+        let equiv_exprs = filtered(ExtractionKind::Dfs);
+        assert!(!equiv_exprs.is_empty());
+        assert!(equiv_exprs.is_subset(&exhaustive));
+        assert!(equiv_exprs.iter().all(|expr| !expr.contains("cos")));
+        assert_eq!(equiv_exprs, filtered(ExtractionKind::Shortest));
+        assert_eq!(equiv_exprs, filtered(ExtractionKind::BottomUp));
+    }
+
+    #[test]
+    fn top_k_extraction() {
+        let exhaustive = extract(ExtractionKind::Dfs, usize::MAX, 5, "(sin x)", |generator| generator);
+        let top_k = |cost: CostKind| {
+            return extract(ExtractionKind::TopK, 5, 5, "(sin x)", |generator| generator.with_cost(cost));
+        };
+
+        /* the cheapest expressions by AST size are the shortest */
+        let equiv_exprs = top_k(CostKind::Size);
+        assert!(equiv_exprs.contains("sin x"));
+        assert!(equiv_exprs.is_subset(&exhaustive));
+        assert_shortest_first(&equiv_exprs, &exhaustive);
+        assert_eq!(equiv_exprs, top_k(CostKind::Size));
+        assert!(top_k(CostKind::Trig).is_subset(&exhaustive));
+    }
+
+    #[test]
+    fn sample_extraction() {
+        let exhaustive = extract(ExtractionKind::Dfs, usize::MAX, 5, "(sin x)", |generator| generator);
+        let sample = |seed: u64| {
+            return extract(ExtractionKind::Sample, 5, 5, "(sin x)", |generator| generator.with_seed(seed));
+        };
+
+        let sampled = sample(1);
+        assert_eq!(sampled.len(), 5.min(exhaustive.len()));
+        assert!(sampled.is_subset(&exhaustive));
+        assert_eq!(sampled, sample(1));
+
+        /* the candidate pool of a diverse selection is sampled in full */
+        let mut sampler = Generator::new(false, 5, 5, 5, 60)
+            .with_extraction(ExtractionKind::Sample)
+            .with_selection(SelectionKind::Edit, 2)
+            .with_seed(1);
+        assert_eq!(sampler.generate_exprs("(sin x)").unwrap().len(), 5);
+        assert_eq!(sampler.get_stats().n_candidates, 10.min(rm_ac_dupes(&exhaustive).len()));
+
+        /* a round stops after MAX_DRAWS_PER_TARGET draws per target when every sample is filtered */
+        let mut sampler = Generator::new(false, 2, 12, 12, 60)
+            .with_extraction(ExtractionKind::Sample)
+            .with_filter(DepthFilter { max_depth: 0 });
+        assert!(sampler.generate_exprs("(* (sin x) (cos x))").unwrap().is_empty());
+        assert!(!sampler.get_stats().time_limit_reached());
+    }
+
+    #[test]
+    fn unreachable_exprs() {
+        let mut generator = Generator::new(false, 1_000, 3, 4, 60).with_skip_unreachable(true);
+        assert!(generator.generate_exprs("(sin x)").unwrap().is_empty());
+        assert_eq!(generator.get_stats().stop_reason, GenStopReason::Unreachable);
+        assert_eq!(generator.get_stats().term_counts.len(), 5);
+        assert!(generator.get_stats().rounds.is_empty());
+    }
+
+    #[test]
+    fn raise_token_limit() {
+        let first_token_limit = |raise_token_limit: bool| {
+            let mut generator = Generator::new(false, 3, 1, 5, 60)
+                .with_extraction(ExtractionKind::BottomUp)
+                .with_raise_token_limit(raise_token_limit);
+            generator.generate_exprs("(sin x)").unwrap();
+            return generator.get_stats().rounds[0].token_limit;
+        };
+
+        assert_eq!(first_token_limit(false), 1);
+        assert!(first_token_limit(true) > 1);
+    }
+
+    #[test]
+    fn diverse_selection() {
+        let mut generator = Generator::new(false, 3, 5, 5, 60).with_selection(SelectionKind::Edit, 2).with_early_stop(None);
+        let equiv_exprs = generator.generate_exprs("(sin x)").unwrap();
+        assert_eq!(equiv_exprs.len(), 3);
+        assert!(generator.get_stats().n_candidates >= equiv_exprs.len());
+        assert!(equiv_exprs.contains("sin x"));
+    }
+
+    #[test]
+    fn early_stop() {
+        let mut generator = Generator::new(false, 5, 8, 8, 60).with_extraction(ExtractionKind::Shortest).with_early_stop(Some(2.0));
+        let equiv_exprs = generator.generate_exprs("(* (sin x) (cos x))").unwrap();
+        assert_eq!(equiv_exprs.len(), 10);
+        assert!(generator.get_stats().rounds[0].target_reached);
+
+        let mut generator = Generator::new(false, 5, 8, 8, 60).with_extraction(ExtractionKind::Shortest).with_early_stop(None);
+        assert!(generator.generate_exprs("(* (sin x) (cos x))").unwrap().len() > 10);
+        assert!(!generator.get_stats().rounds[0].target_reached);
+
+        let mut generator = Generator::new(false, 5, 8, 8, 60).with_extraction(ExtractionKind::Shortest);
+        assert!(generator.generate_exprs("(* (sin x) (cos x))").unwrap().len() > 10);
+        assert!(!generator.get_stats().rounds[0].target_reached);
+        assert!(!GenerationConfig::default().early_stop);
+    }
+
+    #[test]
+    fn derivative_free_generation() {
+        let mut generator = Generator::new(false, usize::MAX, 4, 4, 60)
+            .with_extraction(ExtractionKind::BottomUp)
+            .with_derivative_free(true);
+        let equiv_exprs = generator.generate_exprs("(d x (sin x))").unwrap();
+        assert!(equiv_exprs.contains("cos x"));
+        assert!(equiv_exprs.iter().all(|expr| !expr.split(' ').any(|token| token == "d" || token == "i")));
+
+        let mut generator = Generator::new(false, usize::MAX, 3, 3, 60)
+            .with_extraction(ExtractionKind::BottomUp)
+            .with_derivative_free(true);
+        assert!(generator.generate_exprs("(d x (abs x))").unwrap().is_empty());
+        assert_eq!(generator.get_stats().stop_reason, GenStopReason::Unresolved);
+    }
+
+    #[test]
+    fn budget_cancellation() {
+        let budget = Budget::default();
+        budget.cancel();
+        let mut generator = Generator::new(false, usize::MAX, 12, 12, 600).with_early_stop(None).with_budget(budget);
+        assert!(generator.generate_exprs("(* (sin x) (cos x))").unwrap().is_empty());
+        assert_eq!(generator.get_stats().stop_reason, GenStopReason::Cancelled);
+
+        let mut generator = Generator::new(false, usize::MAX, 12, 12, 600)
+            .with_early_stop(None)
+            .with_budget(Budget::new(Some(std::time::Duration::ZERO)));
+        assert!(generator.generate_exprs("(* (sin x) (cos x))").unwrap().is_empty());
+        assert_eq!(generator.get_stats().stop_reason, GenStopReason::TimeBudget);
+    }
+}
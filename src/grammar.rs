@@ -0,0 +1,275 @@
+use crate::*;
+use std::fmt::{self, Display, Formatter};
+
+/// Grammar Token
+/// token of a sentential form during extraction,
+/// a terminal operator/leaf or a nonterminal eclass
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GrammarToken {
+    /// terminal, the operator (or leaf) of an enode,
+    /// its children are ignored
+    Terminal(Math),
+    /// nonterminal, a canonical eclass
+    NonTerminal(Id),
+}
+
+impl GrammarToken {
+    /// ### member function to check if the token is a nonterminal
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `bool` - whether the token is a nonterminal or not
+    pub fn is_nonterminal(&self) -> bool { return matches!(self, GrammarToken::NonTerminal(_)); }
+}
+
+impl Display for GrammarToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GrammarToken::Terminal(enode) => { write!(f, "{}", enode) },
+            GrammarToken::NonTerminal(id) => { write!(f, "e{}", id) },
+        }
+    }
+}
+
+/// Grammar Struct
+/// context-free grammar generated from an egraph,
+/// every canonical eclass is a nonterminal and
+/// every enode of the eclass is 1 production
+#[derive(Clone, Debug, Default)]
+pub struct Grammar {
+    /// productions of every eclass, in canonical (or seeded) order
+    productions: HashMap<Id, Vec<Math>>,
+}
+
+impl Grammar {
+    /// ### constructor from an egraph, the productions of every eclass
+    /// ### are sorted by operator (or leaf) and child eclass(es), so the
+    /// ### order does not depend on symbol interning or hashing
+    /// #### Argument
+    /// * `egraph` - egraph after equality saturation
+    /// #### Return
+    /// * `Grammar`
+    pub fn from_egraph(egraph: &MathEGraph) -> Self {
+        let productions = egraph
+            .classes()
+            .map(|eclass| {
+                let mut enodes = eclass.nodes.clone();
+                enodes.sort_by_cached_key(|enode| (enode.to_string(), enode.children().to_vec()));
+                return (eclass.id, enodes);
+            })
+            .collect();
+
+        return Grammar { productions };
+    }
+
+    /// ### constructor from the productions of every eclass
+    /// #### Argument
+    /// * `productions` - productions of every canonical eclass
+    /// #### Return
+    /// * `Grammar`
+    pub fn from_productions(productions: HashMap<Id, Vec<Math>>) -> Self {
+        return Grammar { productions };
+    }
+
+    /// ### member function to shuffle the productions of every eclass,
+    /// ### eclasses are visited in id order, so the same seed
+    /// ### always gives the same order, seed 0 keeps the order
+    /// #### Arguments
+    /// * `self`
+    /// * `seed` - random seed
+    /// #### Return
+    /// * `Grammar`
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        if seed == 0 { return self; }
+
+        let mut eclasses: Vec<Id> = self.productions.keys().copied().collect();
+        eclasses.sort();
+        let mut sampler = Sampler::new(seed);
+        for eclass in eclasses {
+            sampler.shuffle(self.productions.get_mut(&eclass).unwrap());
+        }
+
+        return self;
+    }
+
+    /// ### member function to prune the productions of every eclass
+    /// #### Arguments
+    /// * `self`
+    /// * `keep` - whether a production is kept or not
+    /// #### Return
+    /// * `Grammar` - grammar with the kept productions only
+    pub fn filtered(&self, keep: impl Fn(&Math) -> bool) -> Grammar {
+        let productions = self.productions
+            .iter()
+            .map(|(eclass, enodes)| (*eclass, enodes.iter().filter(|enode| keep(enode)).cloned().collect()))
+            .collect();
+
+        return Grammar { productions };
+    }
+
+    /// ### member function to get the productions of an eclass
+    /// #### Arguments
+    /// * `self`
+    /// * `eclass` - canonical eclass
+    /// #### Return
+    /// * `&[Math]` - productions, empty if the eclass is unknown
+    pub fn productions(&self, eclass: Id) -> &[Math] {
+        return self.productions.get(&eclass).map(|enodes| enodes.as_slice()).unwrap_or(&[]);
+    }
+
+    /// ### member function to check if an eclass is a nonterminal
+    /// #### Arguments
+    /// * `self`
+    /// * `eclass` - canonical eclass
+    /// #### Return
+    /// * `bool` - whether the eclass is in the grammar or not
+    pub fn contains(&self, eclass: Id) -> bool { return self.productions.contains_key(&eclass); }
+
+    /// ### member function to get the # of nonterminals
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `usize` - # of nonterminals
+    pub fn len(&self) -> usize { return self.productions.len(); }
+
+    /// ### member function to check if the grammar is empty
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `bool` - whether the grammar is empty or not
+    pub fn is_empty(&self) -> bool { return self.productions.is_empty(); }
+
+    /// ### member function to iterate over nonterminals and their productions
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `Iterator` - (eclass, productions) pairs
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &[Math])> {
+        return self.productions.iter().map(|(eclass, enodes)| (*eclass, enodes.as_slice()));
+    }
+
+    /// ### member function to compute the minimum # of tokens
+    /// ### of any term derived from every eclass (fixpoint iteration),
+    /// ### eclasses without a finite term are left out
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `HashMap<Id, usize>` - minimum # of tokens of every eclass
+    pub fn min_lens(&self) -> HashMap<Id, usize> {
+        let mut min_lens: HashMap<Id, usize> = Default::default();
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (eclass, enodes) in &self.productions {
+                for enode in enodes {
+                    if let Some(len) = production_min_len(&min_lens, enode) {
+                        if min_lens.get(eclass).map_or(true, |min_len| len < *min_len) {
+                            min_lens.insert(*eclass, len);
+                            changed = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        return min_lens;
+    }
+}
+
+/// ### function to compute the minimum # of tokens of a production,
+/// ### 1 token for the operator plus the minimum of every child eclass
+/// #### Arguments
+/// * `min_lens` - minimum # of tokens of every eclass
+/// * `enode` - production
+/// #### Return
+/// * `Option<usize>` - minimum # of tokens, None if a child has no finite term
+pub fn production_min_len(min_lens: &HashMap<Id, usize>, enode: &Math) -> Option<usize> {
+    let mut len: usize = 1;
+    for child in enode.children() {
+        len += min_lens.get(child)?;
+    }
+
+    return Some(len);
+}
+
+/// ### function to expand a production into tokens,
+/// ### the operator followed by the child eclass(es)
+/// #### Argument
+/// * `enode` - production
+/// #### Return
+/// * `Vec<GrammarToken>` - tokens of the production
+pub fn production_tokens(enode: &Math) -> Vec<GrammarToken> {
+    let mut tokens = Vec::with_capacity(enode.len() + 1);
+    tokens.push(GrammarToken::Terminal(enode.clone()));
+    tokens.extend(enode.children().iter().map(|child| GrammarToken::NonTerminal(*child)));
+
+    return tokens;
+}
+
+/// ### function to convert tokens into their string form
+/// #### Argument
+/// * `tokens` - tokens (expression)
+/// #### Return
+/// * `String` - space-separated tokens
+pub fn tokens_to_string(tokens: &[GrammarToken]) -> String {
+    return tokens.iter().map(|token| token.to_string()).collect::<Vec<String>>().join(" ");
+}
+
+impl Display for Grammar {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut eclasses: Vec<&Id> = self.productions.keys().collect();
+        eclasses.sort();
+        for eclass in eclasses {
+            let productions: Vec<String> = self.productions[eclass].iter().map(|enode| tokens_to_string(&production_tokens(enode))).collect();
+            writeln!(f, "e{} -> {:?}", eclass, productions)?;
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grammar_from_egraph() {
+        let mut egraph = MathEGraph::default();
+        let root = egraph.add_expr(&"(sin x)".parse().unwrap());
+        egraph.rebuild();
+        let grammar = Grammar::from_egraph(&egraph);
+
+        assert_eq!(grammar.len(), 2);
+        let tokens = production_tokens(&grammar.productions(root)[0]);
+        assert_eq!(tokens.len(), 2);
+        assert!(!tokens[0].is_nonterminal() && tokens[1].is_nonterminal());
+        assert_eq!(tokens[0].to_string(), "sin");
+        if let GrammarToken::NonTerminal(child) = tokens[1] {
+            assert_eq!(tokens_to_string(&production_tokens(&grammar.productions(child)[0])), "x");
+        }
+    }
+
+    #[test]
+    fn seeded_productions() {
+        let mut egraph = MathEGraph::default();
+        let root = egraph.add_expr(&"(+ x y)".parse().unwrap());
+        let commuted = egraph.add_expr(&"(+ y x)".parse().unwrap());
+        let times_one = egraph.add_expr(&"(* (+ x y) 1)".parse().unwrap());
+        egraph.union(root, commuted);
+        egraph.union(root, times_one);
+        egraph.rebuild();
+        let root = egraph.find(root);
+
+        let grammar = Grammar::from_egraph(&egraph);
+        let ops: Vec<String> = grammar.productions(root).iter().map(|enode| enode.to_string()).collect();
+        assert_eq!(ops, vec!["*", "+", "+"]);
+        assert_eq!(grammar.clone().with_seed(0).productions(root), grammar.productions(root));
+
+        let shuffled = grammar.clone().with_seed(7);
+        assert_eq!(shuffled.productions(root), grammar.clone().with_seed(7).productions(root));
+        let mut sorted = shuffled.productions(root).to_vec();
+        sorted.sort_by_cached_key(|enode| (enode.to_string(), enode.children().to_vec()));
+        assert_eq!(sorted, grammar.productions(root));
+    }
+}
@@ -0,0 +1,324 @@
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use crate::util::{HashMap, HashSet};
+use crate::{Analysis, Budget, CostFunction, EGraph, Id, Language, RecExpr};
+
+/// A term in the k-best list of an e-class, sharing its subterms
+/// with the k-best lists of the child e-classes.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct KBestTerm<L> {
+    node: L,
+    children: Vec<Rc<KBestTerm<L>>>,
+}
+
+impl<L: Language> KBestTerm<L> {
+    fn add_to(&self, expr: &mut RecExpr<L>) -> Id {
+        let ids: Vec<Id> = self.children.iter().map(|child| child.add_to(expr)).collect();
+        let mut ids = ids.into_iter();
+        expr.add(self.node.clone().map_children(|_| ids.next().unwrap()))
+    }
+
+    fn to_recexpr(&self) -> RecExpr<L> {
+        let mut expr = RecExpr::default();
+        self.add_to(&mut expr);
+        expr
+    }
+}
+
+/// The k-best list of an e-class, in order of cost.
+type KBestList<C, L> = Vec<(C, Rc<KBestTerm<L>>)>;
+
+/** Extracting the k cheapest distinct [`RecExpr`]s of every e-class.
+
+Unlike [`Extractor`](crate::Extractor), which keeps only the cheapest
+e-node of every e-class, the `KBestExtractor` keeps the `k` cheapest
+terms of every e-class. The lists are computed bottom-up until a fixpoint:
+the candidates of an e-node are enumerated lazily in order of cost from the
+lists of its children, so every e-node costs `O(k)` cost evaluations per pass.
+Ties are broken by the terms themselves, so the result is deterministic.
+
+The cost function needs to be strictly _monotonic_ (every e-node costs more
+than any of its children), otherwise the fixpoint may not be reached on
+cyclic e-graphs.
+**/
+pub struct KBestExtractor<CF: CostFunction<L>, L: Language> {
+    cost_function: CF,
+    k: usize,
+    nodes: HashMap<Id, Vec<L>>,
+    terms: HashMap<Id, KBestList<CF::Cost, L>>,
+    budget: Budget,
+    timed_out: bool,
+}
+
+impl<CF, L> KBestExtractor<CF, L>
+where
+    CF: CostFunction<L>,
+    L: Language,
+{
+    /// Create a new `KBestExtractor` given an `EGraph`, a
+    /// `CostFunction` and the number of terms `k` per e-class.
+    ///
+    /// The extraction does all the work on creation.
+    pub fn new<N: Analysis<L>>(egraph: &EGraph<L, N>, cost_function: CF, k: usize) -> Self {
+        let classes = egraph.classes().map(|class| (class.id, class.nodes.clone()));
+        Self::from_classes(classes, cost_function, k)
+    }
+
+    /// Create a new `KBestExtractor` given the e-nodes of every
+    /// (canonical) e-class, e.g. a subset of the e-nodes of an `EGraph`.
+    pub fn from_classes<I>(classes: I, cost_function: CF, k: usize) -> Self
+    where
+        I: IntoIterator<Item = (Id, Vec<L>)>,
+    {
+        Self::from_classes_with_budget(classes, cost_function, k, Budget::default())
+    }
+
+    /// Like [`from_classes`](KBestExtractor::from_classes), but the extraction
+    /// stops once the `Budget` is exhausted (out of time or cancelled),
+    /// the k-best lists are then incomplete, see
+    /// [`timed_out`](KBestExtractor::timed_out).
+    pub fn from_classes_with_budget<I>(classes: I, cost_function: CF, k: usize, budget: Budget) -> Self
+    where
+        I: IntoIterator<Item = (Id, Vec<L>)>,
+    {
+        let mut extractor = KBestExtractor {
+            cost_function,
+            k,
+            nodes: classes.into_iter().collect(),
+            terms: HashMap::default(),
+            budget,
+            timed_out: false,
+        };
+        extractor.find_terms();
+
+        extractor
+    }
+
+    /// Whether the budget was exhausted before the extraction finished.
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    fn check_budget(&mut self) -> bool {
+        if !self.timed_out && self.budget.is_exhausted() {
+            self.timed_out = true;
+        }
+        self.timed_out
+    }
+
+    /// Find the `k` cheapest terms of the given canonical e-class,
+    /// in order of cost.
+    pub fn find_k_best(&self, eclass: Id) -> Vec<(CF::Cost, RecExpr<L>)> {
+        match self.terms.get(&eclass) {
+            Some(terms) => terms
+                .iter()
+                .map(|(cost, term)| (cost.clone(), term.to_recexpr()))
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /// Find the `k` cheapest terms rooted at any of the given e-nodes,
+    /// in order of cost.
+    pub fn find_k_best_of(&mut self, nodes: &[L]) -> Vec<(CF::Cost, RecExpr<L>)> {
+        self.best_of(nodes)
+            .into_iter()
+            .map(|(cost, term)| (cost, term.to_recexpr()))
+            .collect()
+    }
+
+    fn find_terms(&mut self) {
+        let mut eclasses: Vec<Id> = self.nodes.keys().copied().collect();
+        eclasses.sort();
+
+        let mut did_something = true;
+        while did_something {
+            did_something = false;
+
+            for eclass in &eclasses {
+                if self.check_budget() {
+                    return;
+                }
+                let nodes = std::mem::take(self.nodes.get_mut(eclass).unwrap());
+                let terms = self.best_of(&nodes);
+                self.nodes.insert(*eclass, nodes);
+
+                let changed = match self.terms.get(eclass) {
+                    Some(old) => !same_terms(old, &terms),
+                    None => !terms.is_empty(),
+                };
+                if changed {
+                    self.terms.insert(*eclass, terms);
+                    did_something = true;
+                }
+            }
+        }
+    }
+
+    fn best_of(&mut self, nodes: &[L]) -> KBestList<CF::Cost, L> {
+        let mut terms = vec![];
+        for node in nodes {
+            terms.extend(self.node_best(node));
+        }
+        terms.sort_by(|a, b| cmp(&a.0, &b.0).then_with(|| a.1.cmp(&b.1)));
+        terms.truncate(self.k);
+
+        terms
+    }
+
+    /// The `k` cheapest terms rooted at an e-node: a best-first search
+    /// over the ranks of the child terms, starting from the cheapest child terms.
+    fn node_best(&mut self, node: &L) -> KBestList<CF::Cost, L> {
+        let child_terms = match node
+            .children()
+            .iter()
+            .map(|child| self.terms.get(child).map(|terms| terms.as_slice()))
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(child_terms) => child_terms,
+            None => return vec![],
+        };
+
+        // children are renamed by position, so repeated child e-classes get their own costs
+        let mut position = 0;
+        let positional = node.clone().map_children(|_| {
+            position += 1;
+            Id::from(position - 1)
+        });
+        let cost_function = &mut self.cost_function;
+        let mut cost_of = |ranks: &[usize]| {
+            cost_function.cost(&positional, |id| {
+                let i = usize::from(id);
+                child_terms[i][ranks[i]].0.clone()
+            })
+        };
+
+        let mut best = vec![];
+        let start = vec![0; child_terms.len()];
+        let mut seen: HashSet<Vec<usize>> = HashSet::default();
+        let mut frontier = vec![(cost_of(&start), start.clone())];
+        seen.insert(start);
+
+        while best.len() < self.k && !frontier.is_empty() {
+            if self.timed_out || (best.len() % 64 == 0 && self.budget.is_exhausted()) {
+                self.timed_out = true;
+                break;
+            }
+            let i = (0..frontier.len())
+                .min_by(|&a, &b| {
+                    cmp(&frontier[a].0, &frontier[b].0).then_with(|| frontier[a].1.cmp(&frontier[b].1))
+                })
+                .unwrap();
+            let (cost, ranks) = frontier.swap_remove(i);
+
+            for child in 0..ranks.len() {
+                if ranks[child] + 1 < child_terms[child].len() {
+                    let mut next = ranks.clone();
+                    next[child] += 1;
+                    if seen.insert(next.clone()) {
+                        frontier.push((cost_of(&next), next));
+                    }
+                }
+            }
+
+            let children = ranks
+                .iter()
+                .enumerate()
+                .map(|(child, rank)| child_terms[child][*rank].1.clone())
+                .collect();
+            best.push((cost, Rc::new(KBestTerm { node: node.clone(), children })));
+        }
+
+        best
+    }
+}
+
+/// Orders costs like `partial_cmp`, with incomparable costs (`NaN`s)
+/// after every comparable cost instead of panicking.
+fn cmp<T: PartialOrd>(a: &T, b: &T) -> Ordering {
+    a.partial_cmp(b).unwrap_or_else(|| {
+        let is_nan = |x: &T| x.partial_cmp(x).is_none();
+        is_nan(a).cmp(&is_nan(b))
+    })
+}
+
+fn same_terms<C: PartialOrd, L: Ord>(a: &[(C, Rc<KBestTerm<L>>)], b: &[(C, Rc<KBestTerm<L>>)]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|(a, b)| cmp(&a.0, &b.0) == Ordering::Equal && a.1 == b.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn k_best_terms() {
+        let rules: &[Rewrite<SymbolLang, ()>] = &[
+            rewrite!("commute-add"; "(+ ?a ?b)" => "(+ ?b ?a)"),
+            rewrite!("add-0"; "(+ ?a 0)" => "?a"),
+        ];
+        let start = "(+ x 0)".parse().unwrap();
+        let runner = Runner::default().with_expr(&start).run(rules);
+        let root = runner.egraph.find(runner.roots[0]);
+
+        let extractor = KBestExtractor::new(&runner.egraph, AstSize, 4);
+        let terms = extractor.find_k_best(root);
+        let costs: Vec<usize> = terms.iter().map(|(cost, _)| *cost).collect();
+        assert_eq!(costs, vec![1, 3, 3, 5]);
+        assert_eq!(terms[0].1, "x".parse().unwrap());
+
+        let exprs: HashSet<String> = terms.iter().map(|(_, expr)| expr.to_string()).collect();
+        assert_eq!(exprs.len(), 4);
+        assert!(exprs.contains("(+ x 0)") && exprs.contains("(+ 0 x)"));
+    }
+
+    #[test]
+    fn k_best_budget() {
+        let rules: &[Rewrite<SymbolLang, ()>] = &[rewrite!("commute-add"; "(+ ?a ?b)" => "(+ ?b ?a)")];
+        let start = "(+ x (+ y z))".parse().unwrap();
+        let runner = Runner::default().with_expr(&start).run(rules);
+        let root = runner.egraph.find(runner.roots[0]);
+        let classes = || runner.egraph.classes().map(|class| (class.id, class.nodes.clone()));
+
+        let extractor = KBestExtractor::from_classes_with_budget(classes(), AstSize, 4, Budget::default());
+        assert!(!extractor.timed_out());
+        assert_eq!(extractor.find_k_best(root).len(), 4);
+
+        /* a cancelled budget stops the extraction, e.g. from another thread */
+        let budget = Budget::default();
+        budget.cancel();
+        let extractor = KBestExtractor::from_classes_with_budget(classes(), AstSize, 4, budget);
+        assert!(extractor.timed_out());
+        assert!(extractor.find_k_best(root).is_empty());
+
+        let extractor = KBestExtractor::from_classes_with_budget(classes(), AstSize, 4, Budget::new(Some(Duration::ZERO)));
+        assert!(extractor.timed_out());
+    }
+
+    #[test]
+    fn k_best_nan_costs() {
+        struct NanAdd;
+        impl CostFunction<SymbolLang> for NanAdd {
+            type Cost = f64;
+            fn cost<C>(&mut self, enode: &SymbolLang, mut costs: C) -> f64
+            where
+                C: FnMut(Id) -> f64,
+            {
+                let cost = enode.fold(1.0, |sum, id| sum + costs(id));
+                if enode.op.as_str() == "*" { f64::NAN } else { cost }
+            }
+        }
+
+        let rules: &[Rewrite<SymbolLang, ()>] = &[rewrite!("add-mul"; "(+ ?a 0)" => "(* ?a 1)")];
+        let start = "(+ x 0)".parse().unwrap();
+        let runner = Runner::default().with_expr(&start).run(rules);
+        let root = runner.egraph.find(runner.roots[0]);
+
+        let terms = KBestExtractor::new(&runner.egraph, NanAdd, 3).find_k_best(root);
+        assert_eq!(terms.len(), 2);
+        assert_eq!(terms[0], (3.0, "(+ x 0)".parse().unwrap()));
+        assert!(terms[1].0.is_nan());
+        assert_eq!(terms[1].1, "(* x 1)".parse().unwrap());
+    }
+}
@@ -37,19 +37,32 @@ mod macros;
 
 // pub mod tutorials;
 
+mod budget;
+mod canonical;
+mod checkpoint;
 mod cli;
 mod config;
 mod ctx_gr;
 mod dot;
 mod eclass;
 mod egraph;
+mod enumerate;
+mod error;
+mod escalation;
 mod explain;
 mod expr_ext;
+mod expr_iter;
 mod extract;
+mod filter;
 mod generate;
+mod generator;
+mod grammar;
+mod kbest_extract;
 mod language;
 mod logger;
 mod math;
+mod math_cost;
+mod stats;
 mod utils;
 #[cfg(feature = "lp")]
 mod lp_extract;
@@ -58,7 +71,10 @@ mod multipattern;
 mod pattern;
 mod rewrite;
 mod run;
+mod sampler;
+mod selection;
 mod subst;
+mod term_count;
 mod unionfind;
 mod util;
 
@@ -93,30 +109,45 @@ impl std::fmt::Display for Id {
     }
 }
 
-pub(crate) use {explain::Explain, unionfind::UnionFind};
+pub(crate) use {error::panic_message, explain::Explain, unionfind::UnionFind};
 
 pub use {
-    cli::{Cli, CliDtype, parse_args},
+    budget::Budget,
+    canonical::*,
+    checkpoint::{Checkpoint, CheckpointOffsets},
+    cli::{Cli, CostKind, EscalationKind, ExtractionKind, OutputFormat, SchedulerKind, SelectionKind, parse_args},
     config::*,
-    ctx_gr::ContextGrammar,
+    ctx_gr::{ContextGrammar, SaturationConfig, SaturationScheduler},
     dot::Dot,
     eclass::EClass,
     egraph::{EGraph, LanguageMapper, SimpleLanguageMapper},
+    enumerate::{Term, TermEnumerator},
+    error::{ConfigError, GenError},
+    escalation::*,
     explain::{
         Explanation, FlatExplanation, FlatTerm, Justification, TreeExplanation, TreeTerm,
         UnionEqualities,
     },
-    expr_ext::{get_global_skip_ecls, get_global_grammar, get_global_equiv_exprs, extract},
+    expr_iter::{EquivExprIter, prefix_to_recexpr, recexpr_to_tokens, tokens_to_recexpr},
     extract::*,
+    filter::*,
     generate::generate,
+    generator::Generator,
+    kbest_extract::KBestExtractor,
+    grammar::{Grammar, GrammarToken, production_min_len, production_tokens, tokens_to_string},
     language::*,
     logger::*,
     math::{MathEGraph, Math, math_rule},
+    math_cost::*,
     multipattern::*,
     pattern::{ENodeOrVar, Pattern, PatternAst, SearchMatches},
     rewrite::{Applier, Condition, ConditionEqual, ConditionalApplier, Rewrite, Searcher},
     run::*,
+    sampler::Sampler,
+    selection::{edit_distance, operator_distance, select_diverse},
+    stats::{ExprStats, GenStopReason, RoundStats},
     subst::{Subst, Var},
+    term_count::TermCounts,
     util::*,
     utils::*,
 };
@@ -74,18 +74,19 @@ impl Analysis<Math> for ConstantFold {
 
     fn make(egraph: &mut MathEGraph, enode: &Math) -> Self::Data {
         let x = |i: &Id| egraph[*i].data.as_ref().map(|d| d.0);
+        // NaN results (e.g. inf - inf) are not folded
         Some(match enode {
             Math::Constant(c) => (*c, format!("{}", c).parse().unwrap()),
             Math::Add([a, b]) => (
-                x(a)? + x(b)?,
+                NotNan::new(x(a)?.into_inner() + x(b)?.into_inner()).ok()?,
                 format!("(+ {} {})", x(a)?, x(b)?).parse().unwrap(),
             ),
             Math::Sub([a, b]) => (
-                x(a)? - x(b)?,
+                NotNan::new(x(a)?.into_inner() - x(b)?.into_inner()).ok()?,
                 format!("(- {} {})", x(a)?, x(b)?).parse().unwrap(),
             ),
             Math::Mul([a, b]) => (
-                x(a)? * x(b)?,
+                NotNan::new(x(a)?.into_inner() * x(b)?.into_inner()).ok()?,
                 format!("(* {} {})", x(a)?, x(b)?).parse().unwrap(),
             ),
             // Math::Div([a, b]) if x(b) != Some(NotNan::new(0.0).unwrap()) => (
@@ -96,7 +97,7 @@ impl Analysis<Math> for ConstantFold {
                 let base = x(a)?.into_inner(); // Extract inner f64 value
                 let exponent = x(b)?.into_inner(); // Extract inner f64 value
                 (
-                    NotNan::new(base.powf(exponent)).unwrap(), // Reconstruct NotNan<f64>
+                    NotNan::new(base.powf(exponent)).ok()?, // Reconstruct NotNan<f64>
                     format!("(pow {} {})", base, exponent).parse().unwrap(),
                 )
             },
@@ -0,0 +1,149 @@
+use crate::*;
+
+/// ### public function to get the default weight of a math operator,
+/// ### cheap arithmetic < multiplicative < powers & logs < trig < calculus
+/// #### Argument
+/// * `enode` - math enode
+/// #### Return
+/// * `f64` - weight of the operator
+pub fn operator_weight(enode: &Math) -> f64 {
+    return match enode {
+        Math::Constant(_) | Math::Symbol(_) => { 1.0 },
+        Math::Add(_) | Math::Sub(_) => { 1.0 },
+        Math::Mul(_) | Math::Div(_) | Math::Abs(_) => { 2.0 },
+        Math::Pow(_) | Math::Sqrt(_) | Math::Ln(_) | Math::Log(_) => { 3.0 },
+        Math::Diff(_) | Math::Integral(_) => { 5.0 },
+        _ => { 4.0 },
+    };
+}
+
+/// ### public function to check if an enode is a trigonometric
+/// ### or inverse trigonometric function
+/// #### Argument
+/// * `enode` - math enode
+/// #### Return
+/// * `bool` - whether the enode is a trig function or not
+pub fn is_trig(enode: &Math) -> bool {
+    return matches!(enode,
+        Math::Sin(_) | Math::Cos(_) | Math::Tan(_) | Math::Csc(_) | Math::Sec(_) | Math::Cot(_) |
+        Math::ASin(_) | Math::ACos(_) | Math::ATan(_) | Math::ACsc(_) | Math::ASec(_) | Math::ACot(_));
+}
+
+/// Operator Cost Struct
+/// sum of the operator weights of an expression,
+/// default weights from operator_weight, overridable per operator
+#[derive(Clone, Debug, Default)]
+pub struct OperatorCost {
+    /// weights overriding the default weight of an operator
+    weights: HashMap<String, f64>,
+}
+
+impl OperatorCost {
+    /// ### member function to override the weight of an operator
+    /// #### Arguments
+    /// * `self`
+    /// * `op` - operator, e.g. "sin"
+    /// * `weight` - weight of the operator, > 0
+    /// #### Return
+    /// * `OperatorCost`
+    pub fn with_weight(mut self, op: &str, weight: f64) -> Self {
+        self.weights.insert(op.to_string(), weight);
+        return self;
+    }
+}
+
+impl CostFunction<Math> for OperatorCost {
+    type Cost = f64;
+
+    fn cost<C>(&mut self, enode: &Math, mut costs: C) -> Self::Cost
+    where
+        C: FnMut(Id) -> Self::Cost,
+    {
+        let weight = match self.weights.get(&enode.to_string()) {
+            Some(weight) => { *weight },
+            None => { operator_weight(enode) },
+        };
+
+        return enode.fold(weight, |sum, id| sum + costs(id));
+    }
+}
+
+/// Trig Count Struct
+/// # of trig functions of an expression, ties broken by AST size
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TrigCount;
+
+impl CostFunction<Math> for TrigCount {
+    type Cost = (usize, usize);
+
+    fn cost<C>(&mut self, enode: &Math, mut costs: C) -> Self::Cost
+    where
+        C: FnMut(Id) -> Self::Cost,
+    {
+        let init = (is_trig(enode) as usize, 1);
+
+        return enode.fold(init, |(n_trigs, size), id| {
+            let (child_n_trigs, child_size) = costs(id);
+            return (n_trigs + child_n_trigs, size.saturating_add(child_size));
+        });
+    }
+}
+
+/// Depth Penalized Cost Struct
+/// cost, AST size and AST depth of an expression,
+/// compared by cost first
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct DepthPenalizedCost {
+    /// AST size + penalty * AST depth
+    pub cost: f64,
+    /// AST size
+    pub size: usize,
+    /// AST depth
+    pub depth: usize,
+}
+
+/// Depth Penalized Size Struct
+/// AST size plus a penalty per level of AST depth,
+/// prefers flat expressions over nested ones of the same size
+#[derive(Clone, Copy, Debug)]
+pub struct DepthPenalizedSize {
+    /// penalty per level of AST depth
+    pub penalty: f64,
+}
+
+impl Default for DepthPenalizedSize {
+    fn default() -> Self {
+        return DepthPenalizedSize { penalty: DEPTH_PENALTY };
+    }
+}
+
+impl CostFunction<Math> for DepthPenalizedSize {
+    type Cost = DepthPenalizedCost;
+
+    fn cost<C>(&mut self, enode: &Math, mut costs: C) -> Self::Cost
+    where
+        C: FnMut(Id) -> Self::Cost,
+    {
+        let (size, depth) = enode.fold((1, 1), |(size, depth), id| {
+            let child = costs(id);
+            return (size + child.size, depth.max(child.depth + 1));
+        });
+
+        return DepthPenalizedCost { cost: size as f64 + self.penalty * depth as f64, size, depth };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn math_costs() {
+        let expr: RecExpr<Math> = "(* (sin x) (cos (+ x 1)))".parse().unwrap();
+        assert_eq!(OperatorCost::default().cost_rec(&expr), 2.0 + 4.0 + 1.0 + 4.0 + 1.0 + 1.0 + 1.0);
+        assert_eq!(OperatorCost::default().with_weight("sin", 10.0).cost_rec(&expr), 20.0);
+        assert_eq!(TrigCount.cost_rec(&expr), (2, 7));
+        let cost = DepthPenalizedSize { penalty: 0.5 }.cost_rec(&expr);
+        assert_eq!((cost.size, cost.depth, cost.cost), (7, 4, 9.0));
+    }
+}
@@ -0,0 +1,96 @@
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+/// Sampler Struct
+/// seeded pseudo random number generator (SplitMix64),
+/// the same seed always gives the same sequence
+#[derive(Clone, Debug)]
+pub struct Sampler {
+    /// generator state
+    state: u64,
+}
+
+impl Sampler {
+    /// ### default constructor
+    /// #### Argument
+    /// * `seed` - random seed
+    /// #### Return
+    /// * `Sampler`
+    pub fn new(seed: u64) -> Self {
+        return Sampler { state: seed };
+    }
+
+    /// ### member function to get the next random u64
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `u64` - random number
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+        return z ^ (z >> 31);
+    }
+
+    /// ### member function to get a uniformly random BigUint
+    /// ### in range [0, bound) by rejection sampling
+    /// #### Arguments
+    /// * `self`
+    /// * `bound` - exclusive upper bound, > 0
+    /// #### Return
+    /// * `BigUint` - random number
+    pub fn below(&mut self, bound: &BigUint) -> BigUint {
+        assert!(!bound.is_zero(), "empty sampling range");
+        let n_bits = bound.bits();
+        let n_digits = ((n_bits + 63) / 64) as usize;
+        let mask = match n_bits % 64 {
+            0 => { u64::MAX },
+            rem => { (1u64 << rem) - 1 },
+        };
+
+        loop {
+            let mut digits: Vec<u64> = (0..n_digits).map(|_| self.next_u64()).collect();
+            *digits.last_mut().unwrap() &= mask;
+            let value = BigUint::from_slice(&digits.iter().flat_map(|digit| [*digit as u32, (*digit >> 32) as u32]).collect::<Vec<u32>>());
+            if value < *bound { return value; }
+        }
+    }
+
+    /// ### member function to shuffle a slice uniformly at random (Fisher-Yates)
+    /// #### Arguments
+    /// * `self`
+    /// * `items` - items to shuffle in place
+    /// #### Return
+    /// * `None`
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = usize::try_from(self.below(&BigUint::from(i + 1))).unwrap();
+            items.swap(i, j);
+        }
+
+        return;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_sampling() {
+        let total = BigUint::from(1_000_000u64) * BigUint::from(u64::MAX);
+        let sample = |seed: u64| {
+            let mut sampler = Sampler::new(seed);
+            return (0..50).map(|_| sampler.below(&total)).collect::<Vec<BigUint>>();
+        };
+        let indices = sample(7);
+        assert_eq!(indices, sample(7));
+        assert_ne!(indices, sample(8));
+        assert!(indices.iter().all(|index| *index < total));
+
+        let mut sampler = Sampler::new(7);
+        assert!((0..50).all(|_| sampler.below(&BigUint::from(3u32)) < BigUint::from(3u32)));
+    }
+}
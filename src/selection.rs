@@ -0,0 +1,102 @@
+use crate::*;
+
+/// ### public function to compute the edit distance between
+/// ### 2 expressions in prefix notation (insert, delete or replace 1 token)
+/// #### Arguments
+/// * `lhs` - tokens of 1st expression
+/// * `rhs` - tokens of 2nd expression
+/// #### Return
+/// * `f64` - edit distance
+pub fn edit_distance(lhs: &[&str], rhs: &[&str]) -> f64 {
+    let mut prev_row: Vec<usize> = (0..=rhs.len()).collect();
+    let mut row = vec![0; rhs.len() + 1];
+
+    for (i, lhs_token) in lhs.iter().enumerate() {
+        row[0] = i + 1;
+        for (j, rhs_token) in rhs.iter().enumerate() {
+            let replace = prev_row[j] + (lhs_token != rhs_token) as usize;
+            row[j+1] = replace.min(prev_row[j+1] + 1).min(row[j] + 1);
+        }
+        std::mem::swap(&mut prev_row, &mut row);
+    }
+
+    return prev_row[rhs.len()] as f64;
+}
+
+/// ### public function to compute the operator set distance between
+/// ### 2 expressions, the Jaccard distance of their token multisets
+/// #### Arguments
+/// * `lhs` - tokens of 1st expression
+/// * `rhs` - tokens of 2nd expression
+/// #### Return
+/// * `f64` - operator set distance in [0, 1]
+pub fn operator_distance(lhs: &[&str], rhs: &[&str]) -> f64 {
+    let mut counts: HashMap<&str, (usize, usize)> = Default::default();
+    for token in lhs { counts.entry(token).or_default().0 += 1; }
+    for token in rhs { counts.entry(token).or_default().1 += 1; }
+
+    let (intersection, union) = counts
+        .values()
+        .fold((0, 0), |(intersection, union), (n_lhs, n_rhs)| {
+            return (intersection + n_lhs.min(n_rhs), union + n_lhs.max(n_rhs));
+        });
+    if union == 0 { return 0.0; }
+
+    return 1.0 - intersection as f64 / union as f64;
+}
+
+/// ### public function to select the most diverse expressions from a
+/// ### candidate pool by farthest-point selection, starting from the
+/// ### shortest expression, every next expression is the one farthest
+/// ### from all selected expressions (ties go to the shorter expression)
+/// #### Arguments
+/// * `equiv_exprs` - candidate pool of equivalent expressions
+/// * `n` - # of expressions to select
+/// * `distance` - distance between 2 tokenized expressions
+/// #### Return
+/// * `HashSet<String>` - selected expressions, the whole pool if it has ≤ n expressions
+pub fn select_diverse(equiv_exprs: &HashSet<String>, n: usize, distance: fn(&[&str], &[&str]) -> f64) -> HashSet<String> {
+    if equiv_exprs.len() <= n { return equiv_exprs.clone(); }
+
+    /* sorted candidates make the selection independent of the HashSet order */
+    let mut candidates: Vec<Vec<&str>> = equiv_exprs.iter().map(|expr| expr.split_whitespace().collect()).collect();
+    candidates.sort_by(|lhs, rhs| lhs.len().cmp(&rhs.len()).then_with(|| lhs.cmp(rhs)));
+
+    let mut selected = vec![candidates.remove(0)];
+    let mut min_dists: Vec<f64> = candidates.iter().map(|candidate| distance(candidate, &selected[0])).collect();
+
+    while selected.len() < n {
+        let mut farthest = 0;
+        for (i, min_dist) in min_dists.iter().enumerate() {
+            if *min_dist > min_dists[farthest] { farthest = i; }
+        }
+        min_dists.remove(farthest);
+        let expr = candidates.remove(farthest);
+        for (candidate, min_dist) in candidates.iter().zip(min_dists.iter_mut()) {
+            *min_dist = min_dist.min(distance(candidate, &expr));
+        }
+        selected.push(expr);
+    }
+
+    return selected.into_iter().map(|tokens| tokens.join(" ")).collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diverse_selection() {
+        assert_eq!(edit_distance(&["+", "x", "y"], &["+", "y", "x"]), 2.0);
+        assert_eq!(operator_distance(&["+", "x", "y"], &["+", "y", "x"]), 0.0);
+        assert_eq!(operator_distance(&["sin", "x"], &["cos", "x"]), 1.0 - 1.0 / 3.0);
+
+        let equiv_exprs: HashSet<String> = ["sin x", "sin + x 0", "sin + 0 x", "cos - x / pi 2"]
+            .iter()
+            .map(|expr| expr.to_string())
+            .collect();
+        let selected = select_diverse(&equiv_exprs, 2, edit_distance);
+        assert_eq!(selected, ["sin x", "cos - x / pi 2"].iter().map(|expr| expr.to_string()).collect());
+        assert_eq!(select_diverse(&equiv_exprs, 4, operator_distance), equiv_exprs);
+    }
+}
@@ -0,0 +1,166 @@
+use crate::*;
+use serde_json::{json, Value};
+
+/// Generation Stop Reason
+/// why the token/time limit escalation stopped
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GenStopReason {
+    /// generation has not finished
+    #[default]
+    Running,
+    /// number of equivalent expressions is reached
+    NEquivExprs,
+    /// maximum token limit is reached
+    MaxTokenLimit,
+    /// total time budget across rounds is spent
+    TimeBudget,
+    /// generation is cancelled through its budget
+    Cancelled,
+    /// a round adds no new equivalent expression
+    NoProgress,
+    /// fewer terms than the number of equivalent expressions
+    /// within the maximum token limit
+    Unreachable,
+    /// no derivative-free term of the root eclass within
+    /// the maximum token limit in derivative-free mode
+    Unresolved,
+}
+
+/// Round Statistics Struct
+/// store metadata of 1 extraction round with a token limit
+#[derive(Clone, Debug, Default)]
+pub struct RoundStats {
+    /// token limit of the round
+    pub token_limit: u16,
+    /// time limit of the round in sec
+    pub time_limit: u32,
+    /// extraction time in sec
+    pub extraction_time: f64,
+    /// # of expression(s) before post-processing (see Canonicalization)
+    pub n_exprs_before: usize,
+    /// # of expression(s) after post-processing (see Canonicalization)
+    pub n_exprs_after: usize,
+    /// whether the time limit fired during extraction
+    pub time_limit_reached: bool,
+    /// whether extraction stopped early with enough distinct expressions
+    pub target_reached: bool,
+}
+
+impl RoundStats {
+    /// ### member function to convert statistics to JSON
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `Value` - JSON object of statistics
+    pub fn to_json(&self) -> Value {
+        return json!({
+            "token_limit": self.token_limit,
+            "time_limit": self.time_limit,
+            "extraction_time": self.extraction_time,
+            "n_exprs_before": self.n_exprs_before,
+            "n_exprs_after": self.n_exprs_after,
+            "time_limit_reached": self.time_limit_reached,
+            "target_reached": self.target_reached,
+        });
+    }
+}
+
+/// Expression Statistics Struct
+/// store per-expression metadata of a generation
+#[derive(Clone, Debug, Default)]
+pub struct ExprStats {
+    /// token limit of the last extraction
+    pub token_limit: u16,
+    /// total generation time in sec
+    pub time: f64,
+    /// total # of e-class(es)
+    pub n_eclasses: usize,
+    /// total # of e-node(s)
+    pub n_enodes: usize,
+    /// reason the equality saturation stopped
+    pub saturation: Option<StopReason>,
+    /// # of equality saturation iteration(s)
+    pub n_iterations: usize,
+    /// equality saturation time in sec
+    pub saturation_time: f64,
+    /// grammar creation time in sec
+    pub grammar_time: f64,
+    /// # of terms of the root eclass by # of tokens up to the maximum token limit,
+    /// decimal strings since the counts can exceed u64
+    pub term_counts: Vec<String>,
+    /// statistics of every extraction round
+    pub rounds: Vec<RoundStats>,
+    /// # of candidate expression(s) before selection
+    pub n_candidates: usize,
+    /// reason the generation stopped
+    pub stop_reason: GenStopReason,
+}
+
+impl ExprStats {
+    /// ### member function to check if the time limit fired in any round
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `bool` - whether the time limit fired or not
+    pub fn time_limit_reached(&self) -> bool {
+        return self.rounds.iter().any(|round| round.time_limit_reached);
+    }
+
+    /// ### member function to check if the generation is reproducible,
+    /// ### i.e. no time limit or budget of saturation or extraction fired
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `bool` - whether the generation is reproducible or not
+    pub fn is_reproducible(&self) -> bool {
+        return !matches!(self.saturation, Some(StopReason::TimeLimit(_)) | Some(StopReason::Other(_))) &&
+            !self.time_limit_reached() &&
+            !matches!(self.stop_reason, GenStopReason::TimeBudget | GenStopReason::Cancelled);
+    }
+
+    /// ### member function to convert statistics to JSON
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `Value` - JSON object of statistics
+    pub fn to_json(&self) -> Value {
+        let saturation = match &self.saturation {
+            Some(stop_reason) => { format!("{:?}", stop_reason) },
+            None => { "None".to_string() },
+        };
+
+        return json!({
+            "token_limit": self.token_limit,
+            "time": self.time,
+            "n_eclasses": self.n_eclasses,
+            "n_enodes": self.n_enodes,
+            "saturation": saturation,
+            "n_iterations": self.n_iterations,
+            "saturation_time": self.saturation_time,
+            "grammar_time": self.grammar_time,
+            "term_counts": self.term_counts,
+            "rounds": self.rounds.iter().map(|round| round.to_json()).collect::<Vec<Value>>(),
+            "n_candidates": self.n_candidates,
+            "time_limit_reached": self.time_limit_reached(),
+            "stop_reason": format!("{:?}", self.stop_reason),
+        });
+    }
+
+    /// ### member function to convert statistics to JSON
+    /// ### without wall-clock times, the same on every reproducible run
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `Value` - JSON object of statistics
+    pub fn to_untimed_json(&self) -> Value {
+        let mut stats = self.to_json();
+        for key in ["time", "saturation_time", "grammar_time"] {
+            stats.as_object_mut().unwrap().remove(key);
+        }
+        for round in stats["rounds"].as_array_mut().unwrap() {
+            round.as_object_mut().unwrap().remove("extraction_time");
+        }
+
+        return stats;
+    }
+}
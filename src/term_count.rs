@@ -0,0 +1,223 @@
+use crate::*;
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+/// Term Counts Struct
+/// # of distinct terms of every eclass by # of tokens,
+/// counted bottom-up over the grammar up to a token bound,
+/// every term can be unranked from its index
+pub struct TermCounts {
+    /// token bound
+    max_len: usize,
+    /// kept productions of every eclass
+    productions: HashMap<Id, Vec<Math>>,
+    /// # of terms of every eclass, index n is the # of terms of n tokens
+    counts: HashMap<Id, Vec<BigUint>>,
+}
+
+impl TermCounts {
+    /// ### constructor to count the terms of every eclass
+    /// #### Arguments
+    /// * `grammar` - grammar from MathEGraph
+    /// * `max_len` - token bound
+    /// #### Return
+    /// * `TermCounts`
+    pub fn new(grammar: &Grammar, max_len: usize) -> Self {
+        let productions: HashMap<Id, Vec<Math>> = grammar.iter().map(|(eclass, enodes)| (eclass, enodes.to_vec())).collect();
+        let mut term_counts = TermCounts {
+            max_len,
+            counts: productions.keys().map(|eclass| (*eclass, vec![BigUint::zero(); max_len + 1])).collect(),
+            productions,
+        };
+
+        /* a term of n tokens only has subterms of < n tokens */
+        for n_tokens in 1..=max_len {
+            let counts: Vec<(Id, BigUint)> = term_counts.productions
+                .iter()
+                .map(|(eclass, enodes)| (*eclass, term_counts.productions_count(enodes, n_tokens)))
+                .collect();
+            for (eclass, count) in counts {
+                term_counts.counts.get_mut(&eclass).unwrap()[n_tokens] = count;
+            }
+        }
+
+        return term_counts;
+    }
+
+    /// ### member function to get the token bound
+    /// #### Argument
+    /// * `self`
+    /// #### Return
+    /// * `max_len` - token bound
+    pub fn get_max_len(&self) -> usize { return self.max_len; }
+
+    /// ### member function to get the # of terms of an eclass
+    /// ### with exactly `n_tokens` tokens
+    /// #### Arguments
+    /// * `self`
+    /// * `eclass` - canonical eclass
+    /// * `n_tokens` - # of tokens
+    /// #### Return
+    /// * `BigUint` - # of terms, 0 beyond the token bound
+    pub fn count(&self, eclass: Id, n_tokens: usize) -> BigUint {
+        return match self.counts.get(&eclass).and_then(|counts| counts.get(n_tokens)) {
+            Some(count) => { count.clone() },
+            None => { BigUint::zero() },
+        };
+    }
+
+    /// ### member function to get the # of terms derived from
+    /// ### any of the productions with exactly `n_tokens` tokens
+    /// #### Arguments
+    /// * `self`
+    /// * `enodes` - productions
+    /// * `n_tokens` - # of tokens
+    /// #### Return
+    /// * `BigUint` - # of terms
+    pub fn productions_count(&self, enodes: &[Math], n_tokens: usize) -> BigUint {
+        return enodes.iter().map(|enode| self.children_count(enode.children(), n_tokens.saturating_sub(1))).sum();
+    }
+
+    /// ### member function to get the # of terms derived from
+    /// ### any of the productions by # of tokens
+    /// #### Arguments
+    /// * `self`
+    /// * `enodes` - productions
+    /// #### Return
+    /// * `Vec<BigUint>` - # of terms, index n is the # of terms of n tokens
+    pub fn len_counts(&self, enodes: &[Math]) -> Vec<BigUint> {
+        return (0..=self.max_len).map(|n_tokens| self.productions_count(enodes, n_tokens)).collect();
+    }
+
+    /// ### private member function to get the # of ways to derive
+    /// ### the child eclass(es) with `n_tokens` tokens in total
+    /// #### Arguments
+    /// * `self`
+    /// * `children` - child eclass(es)
+    /// * `n_tokens` - # of tokens of all child eclass(es)
+    /// #### Return
+    /// * `BigUint` - # of ways
+    fn children_count(&self, children: &[Id], n_tokens: usize) -> BigUint {
+        match children {
+            [] => { return if n_tokens == 0 { BigUint::one() } else { BigUint::zero() }; },
+            [child] => { return self.count(*child, n_tokens); },
+            [child, rest @ ..] => {
+                let mut count = BigUint::zero();
+                for child_len in 1..n_tokens {
+                    let child_count = self.count(*child, child_len);
+                    if child_count.is_zero() { continue; }
+                    count += child_count * self.children_count(rest, n_tokens - child_len);
+                }
+                return count;
+            },
+        }
+    }
+
+    /// ### member function to get the term of the productions
+    /// ### with exactly `n_tokens` tokens at an index,
+    /// ### terms are ordered by production, then by child sizes,
+    /// ### then by child terms
+    /// #### Arguments
+    /// * `self`
+    /// * `enodes` - productions
+    /// * `n_tokens` - # of tokens
+    /// * `index` - term index, < productions_count(enodes, n_tokens)
+    /// #### Return
+    /// * `Vec<GrammarToken>` - tokens of the term
+    pub fn unrank(&self, enodes: &[Math], n_tokens: usize, index: &BigUint) -> Vec<GrammarToken> {
+        let mut tokens = vec![];
+        self.unrank_productions(enodes, n_tokens, index.clone(), &mut tokens);
+
+        return tokens;
+    }
+
+    /// ### private member function to append the term of the productions
+    /// ### at an index into tokens
+    /// #### Arguments
+    /// * `self`
+    /// * `enodes` - productions
+    /// * `n_tokens` - # of tokens
+    /// * `index` - term index
+    /// * `tokens` - tokens to append to
+    /// #### Return
+    /// * `None`
+    fn unrank_productions(&self, enodes: &[Math], n_tokens: usize, mut index: BigUint, tokens: &mut Vec<GrammarToken>) {
+        for enode in enodes {
+            let count = self.children_count(enode.children(), n_tokens - 1);
+            if index < count {
+                tokens.push(GrammarToken::Terminal(enode.clone()));
+                self.unrank_children(enode.children(), n_tokens - 1, index, tokens);
+                return;
+            }
+            index -= count;
+        }
+
+        panic!("term index out of range");
+    }
+
+    /// ### private member function to append the terms of the child eclass(es)
+    /// ### with `n_tokens` tokens in total at an index into tokens
+    /// #### Arguments
+    /// * `self`
+    /// * `children` - child eclass(es)
+    /// * `n_tokens` - # of tokens of all child eclass(es)
+    /// * `index` - index of the child terms
+    /// * `tokens` - tokens to append to
+    /// #### Return
+    /// * `None`
+    fn unrank_children(&self, children: &[Id], n_tokens: usize, mut index: BigUint, tokens: &mut Vec<GrammarToken>) {
+        match children {
+            [] => {},
+            [child] => { self.unrank_productions(&self.productions[child], n_tokens, index, tokens); },
+            [child, rest @ ..] => {
+                for child_len in 1..n_tokens {
+                    let child_count = self.count(*child, child_len);
+                    let rest_count = self.children_count(rest, n_tokens - child_len);
+                    let count = &child_count * &rest_count;
+                    if index < count {
+                        /* child index is the major digit, rest index the minor digit */
+                        let child_index = &index / &rest_count;
+                        let rest_index = &index % &rest_count;
+                        self.unrank_productions(&self.productions[child], child_len, child_index, tokens);
+                        self.unrank_children(rest, n_tokens - child_len, rest_index, tokens);
+                        return;
+                    }
+                    index -= count;
+                }
+                panic!("term index out of range");
+            },
+        }
+
+        return;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_and_unrank() {
+        let mut egraph = MathEGraph::default();
+        let x = egraph.add_expr(&"x".parse().unwrap());
+        let y = egraph.add_expr(&"y".parse().unwrap());
+        let root = egraph.add_expr(&"(+ x y)".parse().unwrap());
+        let swapped = egraph.add_expr(&"(+ y x)".parse().unwrap());
+        egraph.union(x, y);
+        egraph.union(root, swapped);
+        egraph.rebuild();
+        let root = egraph.find(root);
+        let grammar = Grammar::from_egraph(&egraph);
+        let term_counts = TermCounts::new(&grammar, 3);
+
+        /* x | y, and + {x, y} {x, y} */
+        assert_eq!(term_counts.count(root, 1), BigUint::zero());
+        assert_eq!(term_counts.count(root, 3), BigUint::from(4u32));
+        assert_eq!(term_counts.len_counts(grammar.productions(root)), vec![BigUint::zero(), BigUint::zero(), BigUint::zero(), BigUint::from(4u32)]);
+        let terms: HashSet<String> = (0..4u32)
+            .map(|index| tokens_to_string(&term_counts.unrank(grammar.productions(root), 3, &BigUint::from(index))))
+            .collect();
+        assert_eq!(terms.len(), 4);
+        assert!(terms.contains("+ x y") && terms.contains("+ y y"));
+    }
+}
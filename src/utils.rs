@@ -1,105 +1,17 @@
 use crate::*;
-use std::process::exit;
 
-/// ### public function to set global variable optimized (optimized extraction flag)
+/// ### public function to sort equivalent expressions, shorter expressions
+/// ### (# of tokens) first, then in lexicographic order, so the order
+/// ### does not depend on the HashSet
 /// #### Argument
-/// * `optimized` - optimized extraction flag
+/// * `equiv_exprs` - equivalent expressions
 /// #### Return
-/// * `None`
-pub unsafe fn set_optimized_flag(optimized: bool) {
-    OPTIMIZED = optimized;
-    return;
-}
-
-/// ### public function to set global variable num_equiv_exprs
-/// #### Argument
-/// * `token_limit` - token limit
-/// #### Return
-/// * `None`
-pub unsafe fn set_n_equiv_exprs(n_equiv_exprs: u8) {
-    N_EQUIV_EXPRS = n_equiv_exprs;
-    return;
-}
-
-/// ### public function to set global variable token_limit
-/// #### Argument
-/// * `token_limit` - token_limit
-/// #### Return
-/// * `None`
-pub unsafe fn set_token_limit(token_limit: u8) {
-    TOKEN_LIMIT = token_limit;
-    return;
-}
-
-/// ### public function to set global variable max_token_limit
-/// #### Argument
-/// * `max_token_limit` - maximum token limit
-/// #### Return
-/// * `None`
-pub unsafe fn set_max_token_limit(max_token_limit: u8) {
-    MAX_TOKEN_LIMIT = max_token_limit;
-    return;
-}
+/// * `Vec<&String>` - sorted equivalent expressions
+pub fn sort_exprs(equiv_exprs: &HashSet<String>) -> Vec<&String> {
+    let mut sorted: Vec<(usize, &String)> = equiv_exprs.iter().map(|expr| (expr.split_whitespace().count(), expr)).collect();
+    sorted.sort();
 
-/// ### public function to set global variable time limit
-/// #### Argument
-/// * `time_limit` - token limit
-/// #### Return
-/// * `None`
-pub unsafe fn set_time_limit(time_limit: u16) {
-    TIME_LIMIT = time_limit;
-    return;
-}
-
-/// ### public function to set global variable start_time
-/// #### Argument
-/// * `start_time` - start time
-/// #### Return
-/// * `None`
-pub unsafe fn set_start_time(start_time: Instant) {
-    START_TIME = Some(start_time);
-    return;
-}
-
-/// ### public function to get global variable start_time
-/// #### Argument
-/// * `None`
-/// #### Return
-/// * `Instant` - current time
-pub unsafe fn get_start_time() -> Instant {
-    let start_time = match START_TIME {
-        Some(start_time) => { start_time },
-        _ => {
-            log_error(&format!("Failed to unwrap global variable START_TIME '{:?}'.\n", START_TIME));
-            exit(1);
-        },
-    };
-
-    return start_time;
-}
-
-/// ### public function to remove permutations from the final results
-/// ### of equivalent expression
-/// #### Argument
-/// * `equiv_exprs` - deduplicate results of equivalent expressions
-/// #### Return
-/// * `HashSet<String>` - distinct equivalent expressions
-pub fn rm_permu(equiv_exprs: &HashSet<String>) -> HashSet<String> {
-    let mut expr_mapping = HashMap::default();
-
-    for expr in equiv_exprs.clone().into_iter() {
-        let mut tokens: Vec<&str> = expr.split_whitespace().collect();
-        tokens.sort();
-        let expr_sort: String = tokens.join(" ");
-
-        if !expr_mapping.contains_key(&expr_sort) {
-            expr_mapping.insert(expr_sort, expr);
-        }
-    }
-
-    let equiv_exprs_distinct = expr_mapping.into_values().collect();
-
-    return equiv_exprs_distinct;
+    return sorted.into_iter().map(|(_, expr)| expr).collect();
 }
 
 /// ### public function to print the type of a variable
@@ -171,29 +83,16 @@ pub fn pt_root_ecls_info(root_ecls: &Vec<Id>) {
     return;
 }
 
-/// ### public function to print eclass(es) to skip during extraction
-/// #### Argument
-/// * `skip_ecls` - vec<String> to skip during extraction
-/// #### Return
-/// * `None`
-pub fn pt_skip_ecls(skip_ecls: &HashMap<String, f64>) {
-    log_debug_raw("\n");
-    log_debug("---------- Skip EClasses ----------\n");
-    log_debug(&format!("{:?}\n", skip_ecls));
-    log_debug("-----------------------------------\n");
-    return;
-}
-
 /// ### public function to print grammar
 /// #### Argument
-/// * `grammar` - grammar HashMap
+/// * `grammar` - grammar
 /// #### Return
 /// * `None`
-pub fn pt_grammar(grammar: &HashMap<String, Vec<String>>){
+pub fn pt_grammar(grammar: &Grammar){
     log_debug_raw("\n");
     log_debug("------------- Grammar -------------\n");
-    for (eclass, rewrite) in grammar {
-        log_debug(&format!("{} -> {:?}\n", eclass, rewrite));
+    for line in grammar.to_string().lines() {
+        log_debug(&format!("{}\n", line));
     }
     log_debug("-----------------------------------\n");
     return;
@@ -201,12 +100,13 @@ pub fn pt_grammar(grammar: &HashMap<String, Vec<String>>){
 
 /// ### public function to print initial rewrites
 /// #### Argument
-/// * `init_rw` - init_rw Vec<String>
+/// * `init_rw` - initial rewrites of a root eclass
 /// #### Return
 /// * `None`
-pub fn pt_init_rw(init_rw: &Vec<String>) {
+pub fn pt_init_rw(init_rw: &[Math]) {
     log_debug_raw("\n");
     log_debug("--------- Initial Rewrite ---------\n");
+    let init_rw: Vec<String> = init_rw.iter().map(|rw| tokens_to_string(&production_tokens(rw))).collect();
     log_debug(&format!("{:?}\n", init_rw));
     log_debug("-----------------------------------\n");
     return;